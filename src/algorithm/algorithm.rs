@@ -1,14 +1,17 @@
 use rand::Rng;
 
-use crate::graph::{BinGraph, Graph};
+use crate::graph::{BinGraph, BinStorage, Graph};
+use crate::Error;
+
+use super::Observer;
 
 // Generic interface for algorithms that solve the graphical balls and bins problem
 pub trait LoadBalanceAlgorithm<G: Graph> {
-    fn for_graph(graph: &BinGraph<G>) -> Self;
+    fn for_graph(graph: &BinGraph<G, impl BinStorage>) -> Self;
     // Returns true to indicate throwing a ball into bin 'u'
     fn choose_between(
         &mut self,
-        graph: &BinGraph<G>,
+        graph: &BinGraph<G, impl BinStorage>,
         u: G::Vertex,
         v: G::Vertex,
         rng: &mut impl Rng,
@@ -17,25 +20,105 @@ pub trait LoadBalanceAlgorithm<G: Graph> {
 
 // Throws 'balls' additional balls into an existing graphical balls and bins setup
 pub fn load_balance<G: Graph, A: LoadBalanceAlgorithm<G>, R: Rng>(
-    graph: &mut BinGraph<G>,
+    graph: &mut BinGraph<G, impl BinStorage>,
     balls: u64,
     rng: &mut R,
+) {
+    load_balance_observed::<G, A, R, _>(graph, balls, rng, &mut ())
+}
+
+// Same as 'load_balance' but notifies 'observer' after each ball is thrown
+pub fn load_balance_observed<G: Graph, A: LoadBalanceAlgorithm<G>, R: Rng, O: Observer<G>>(
+    graph: &mut BinGraph<G, impl BinStorage>,
+    balls: u64,
+    rng: &mut R,
+    observer: &mut O,
 ) {
     let mut algorithm = A::for_graph(graph);
-    for _ in 0..balls {
+    for step in 1..=balls {
         let (u, v) = graph.random_edge(rng);
-        if algorithm.choose_between(graph, u, v, rng) {
-            graph[u] += 1
+        let chosen = if algorithm.choose_between(graph, u, v, rng) {
+            u
         } else {
-            graph[v] += 1
-        }
+            v
+        };
+        graph.add_ball(chosen);
+        observer.observe(graph, (u, v), chosen, step);
     }
 }
 
 // Runs the graphical balls and bins process with 'balls' balls on the graph 'g'
-pub fn load_balanced<G: Graph, A: LoadBalanceAlgorithm<G>>(g: G, balls: u64) -> BinGraph<G> {
-    let mut graph = BinGraph::new(g);
-    let mut rng = rand::thread_rng();
-    load_balance::<G, A, _>(&mut graph, balls, &mut rng);
-    graph
+pub fn load_balanced<G: Graph, A: LoadBalanceAlgorithm<G>, R: Rng>(
+    g: G,
+    balls: u64,
+    rng: &mut R,
+) -> Result<BinGraph<G>, Error> {
+    let mut graph = BinGraph::new(g)?;
+    load_balance::<G, A, _>(&mut graph, balls, rng);
+    Ok(graph)
+}
+
+#[test]
+fn test_reproducible() {
+    use crate::graph::ArbitraryGraph;
+    use crate::rng::seeded;
+
+    let run = |seed| {
+        let mut rng = seeded(seed);
+        let graph = ArbitraryGraph::random(28, 3, &mut rng).unwrap();
+        let graph = load_balanced::<_, crate::algorithm::Greedy, _>(graph, 280, &mut rng).unwrap();
+        graph
+            .iter_vertices()
+            .map(|v| graph.load(v))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(5), run(5));
+}
+
+#[test]
+fn test_observed() {
+    use super::{Every, GapSeries, Greedy};
+    use crate::graph::RingGraph;
+
+    let mut rng = crate::rng::seeded(2);
+    let mut graph: BinGraph<_> = BinGraph::new(RingGraph::new(10).unwrap()).unwrap();
+    let mut observer = (Every::new(7, GapSeries::new()), GapSeries::new());
+    load_balance_observed::<_, Greedy, _, _>(&mut graph, 100, &mut rng, &mut observer);
+    let (every, all) = (observer.0.into_inner(), observer.1);
+    assert_eq!(all.points().len(), 100);
+    assert_eq!(every.points().len(), 14);
+    assert!(every
+        .points()
+        .iter()
+        .all(|p| all.points()[p.step as usize - 1] == *p));
+    let last = all.points().last().unwrap();
+    assert_eq!((last.step, last.gap), (100, graph.gap()));
+    // observing must not change the process
+    let mut rng = crate::rng::seeded(2);
+    let mut unobserved: BinGraph<_> = BinGraph::new(RingGraph::new(10).unwrap()).unwrap();
+    load_balance::<_, Greedy, _>(&mut unobserved, 100, &mut rng);
+    assert!(graph
+        .iter_vertices()
+        .all(|v| graph.load(v) == unobserved.load(v)));
+}
+
+#[test]
+fn test_storage_equivalent() {
+    use super::Greedy;
+    use crate::graph::{HyperCubeGraph, RelativeBins, WideBins};
+
+    // enough balls that RelativeBins has to renormalise several times
+    let balls = 300 * 64;
+    let mut narrow: BinGraph<_> = BinGraph::new(HyperCubeGraph::new(6).unwrap()).unwrap();
+    let mut wide: BinGraph<_, WideBins> = BinGraph::new(HyperCubeGraph::new(6).unwrap()).unwrap();
+    let mut relative: BinGraph<_, RelativeBins> =
+        BinGraph::new(HyperCubeGraph::new(6).unwrap()).unwrap();
+    load_balance::<_, Greedy, _>(&mut narrow, balls, &mut crate::rng::seeded(4));
+    load_balance::<_, Greedy, _>(&mut wide, balls, &mut crate::rng::seeded(4));
+    load_balance::<_, Greedy, _>(&mut relative, balls, &mut crate::rng::seeded(4));
+    for v in narrow.iter_vertices() {
+        assert_eq!(narrow.load(v), wide.load(v));
+        assert_eq!(narrow.load(v), relative.load(v));
+    }
+    assert_eq!(relative.total_load(), balls);
 }
@@ -1,24 +1,24 @@
 use rand::Rng;
 
-use crate::graph::{BinGraph, Graph};
+use crate::graph::{BinGraph, BinStorage, Graph};
 
 use super::LoadBalanceAlgorithm;
 
 pub struct Greedy;
 
 impl<G: Graph> LoadBalanceAlgorithm<G> for Greedy {
-    fn for_graph(_: &BinGraph<G>) -> Self {
+    fn for_graph(_: &BinGraph<G, impl BinStorage>) -> Self {
         Greedy
     }
     fn choose_between(
         &mut self,
-        graph: &BinGraph<G>,
+        graph: &BinGraph<G, impl BinStorage>,
         u: G::Vertex,
         v: G::Vertex,
         rng: &mut impl Rng,
     ) -> bool {
         // choose the bin with less balls
-        let (u, v) = (graph[u], graph[v]);
+        let (u, v) = (graph.load(u), graph.load(v));
         if u < v {
             true
         } else if u == v {
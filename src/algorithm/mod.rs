@@ -1,5 +1,7 @@
 pub use algorithm::*;
 pub use greedy::Greedy;
+pub use observer::{Every, GapPoint, GapSeries, Observer};
 
 mod algorithm;
 mod greedy;
+mod observer;
@@ -0,0 +1,113 @@
+use crate::graph::{Bin, BinGraph, BinStorage, Graph};
+
+// Watches the balls and bins process as it runs, see 'load_balance_observed'
+pub trait Observer<G: Graph> {
+    // Called after each ball is thrown, 'edge' is the sampled edge, 'chosen' the endpoint the ball
+    // was thrown into, and 'step' the number of balls thrown so far (starting at 1)
+    fn observe(
+        &mut self,
+        graph: &BinGraph<G, impl BinStorage>,
+        edge: (G::Vertex, G::Vertex),
+        chosen: G::Vertex,
+        step: u64,
+    );
+}
+
+// Observes nothing
+impl<G: Graph> Observer<G> for () {
+    fn observe(
+        &mut self,
+        _: &BinGraph<G, impl BinStorage>,
+        _: (G::Vertex, G::Vertex),
+        _: G::Vertex,
+        _: u64,
+    ) {
+    }
+}
+
+// Passes every ball on to both observers
+impl<G: Graph, A: Observer<G>, B: Observer<G>> Observer<G> for (A, B) {
+    fn observe(
+        &mut self,
+        graph: &BinGraph<G, impl BinStorage>,
+        edge: (G::Vertex, G::Vertex),
+        chosen: G::Vertex,
+        step: u64,
+    ) {
+        self.0.observe(graph, edge, chosen, step);
+        self.1.observe(graph, edge, chosen, step);
+    }
+}
+
+// Only passes every k'th ball on to the wrapped observer
+pub struct Every<O> {
+    k: u64,
+    inner: O,
+}
+
+impl<O> Every<O> {
+    pub fn new(k: u64, inner: O) -> Self {
+        assert!(k > 0, "cannot observe every 0th ball");
+        Every { k, inner }
+    }
+
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<G: Graph, O: Observer<G>> Observer<G> for Every<O> {
+    fn observe(
+        &mut self,
+        graph: &BinGraph<G, impl BinStorage>,
+        edge: (G::Vertex, G::Vertex),
+        chosen: G::Vertex,
+        step: u64,
+    ) {
+        if step.is_multiple_of(self.k) {
+            self.inner.observe(graph, edge, chosen, step)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct GapPoint {
+    pub step: u64,
+    pub gap: Bin,
+    pub upper_gap: Bin,
+    pub max_load: Bin,
+}
+
+// Records the gap, upper gap and maximum load each time it observes a ball
+// Wrap in 'Every' to record a point every k balls
+#[derive(Clone, Debug, Default)]
+pub struct GapSeries {
+    points: Vec<GapPoint>,
+}
+
+impl GapSeries {
+    pub fn new() -> Self {
+        GapSeries::default()
+    }
+
+    pub fn points(&self) -> &[GapPoint] {
+        &self.points
+    }
+}
+
+impl<G: Graph> Observer<G> for GapSeries {
+    fn observe(
+        &mut self,
+        graph: &BinGraph<G, impl BinStorage>,
+        _: (G::Vertex, G::Vertex),
+        _: G::Vertex,
+        step: u64,
+    ) {
+        self.points.push(GapPoint {
+            step,
+            gap: graph.gap(),
+            upper_gap: graph.upper_gap(),
+            max_load: graph.max_load(),
+        })
+    }
+}
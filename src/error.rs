@@ -0,0 +1,80 @@
+use std::fmt::{Display, Formatter};
+
+// Invalid parameters for a graph or its bins
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    NoVertices,
+    // balls are thrown along edges, so bins need a graph with at least one
+    NoEdges,
+    // the number of vertices does not fit in a usize
+    TooManyVertices,
+    // every d-regular graph on n vertices has n * d / 2 edges
+    OddDegreeSum { n: usize, d: usize },
+    // a simple graph has no vertex with n or more neighbours
+    DegreeTooLarge { n: usize, d: usize },
+    Irregular,
+    EdgeOutOfRange { u: usize, v: usize, n: usize },
+    SelfLoop { v: usize },
+    RepeatedEdge { u: usize, v: usize },
+    // a group element with the wrong number of coordinates
+    DimensionMismatch { expected: usize, found: usize },
+    // probabilities must be between 0 and 1
+    InvalidProbability,
+    // weights must be finite and non-negative
+    InvalidWeights,
+    // power laws need an exponent above 1
+    InvalidExponent,
+    // distances are non-negative
+    InvalidRadius,
+    // there are only n (n - 1) / 2 pairs of vertices
+    TooManyEdges { n: usize, m: usize },
+    // fat-trees have k / 2 servers per edge switch
+    OddArity { k: usize },
+    // LPS graphs need distinct primes p, q = 1 mod 4 with q > 2 sqrt(p)
+    LpsParameters { p: usize, q: usize },
+    // the number of balls to throw on n vertices does not fit in a u64
+    TooManyBalls { n: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NoVertices => write!(f, "graph has no vertices"),
+            Error::NoEdges => write!(f, "graph has no edges"),
+            Error::TooManyVertices => write!(f, "graph has too many vertices"),
+            Error::OddDegreeSum { n, d } => write!(
+                f,
+                "there is no {d}-regular graph on {n} vertices as n * d is odd"
+            ),
+            Error::DegreeTooLarge { n, d } => write!(
+                f,
+                "there is no simple {d}-regular graph on {n} vertices as d >= n"
+            ),
+            Error::Irregular => write!(f, "graph is not regular"),
+            Error::EdgeOutOfRange { u, v, n } => {
+                write!(f, "edge {u} -- {v} is out of range for {n} vertices")
+            }
+            Error::SelfLoop { v } => write!(f, "self loop at {v}"),
+            Error::RepeatedEdge { u, v } => write!(f, "edge {u} -- {v} appears more than once"),
+            Error::DimensionMismatch { expected, found } => {
+                write!(f, "expected {expected} coordinates but found {found}")
+            }
+            Error::InvalidProbability => write!(f, "probability is not between 0 and 1"),
+            Error::InvalidWeights => write!(f, "weights must be finite and non-negative"),
+            Error::InvalidExponent => write!(f, "power law exponent must be above 1"),
+            Error::InvalidRadius => write!(f, "radius must be non-negative"),
+            Error::TooManyEdges { n, m } => {
+                write!(f, "there is no simple graph on {n} vertices with {m} edges")
+            }
+            Error::OddArity { k } => write!(f, "there is no {k}-ary fat-tree as k is odd"),
+            Error::LpsParameters { p, q } => write!(
+                f,
+                "there is no LPS graph for p = {p}, q = {q}, they must be distinct primes which \
+                 are 1 mod 4 with q > 2 sqrt(p)"
+            ),
+            Error::TooManyBalls { n } => write!(f, "too many balls to throw on {n} vertices"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+pub use summary::{GapSummary, SummaryWriter};
+pub use sweep::{BallSpec, Family, Sweep};
+pub use trial::{Trial, TrialResult};
+pub use writer::TrialWriter;
+
+mod summary;
+mod sweep;
+mod trial;
+mod writer;
+
+// Number of threads to use when none is requested
+pub fn default_threads() -> NonZeroUsize {
+    thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+}
+
+// Runs 'trials' on 'threads' worker threads, writing out the results in the order of 'trials'
+// Each trial is seeded independently so the results do not depend on the number of threads
+pub fn run_trials<W: Write>(
+    trials: &[Trial],
+    threads: NonZeroUsize,
+    out: &mut TrialWriter<W>,
+) -> io::Result<()> {
+    for_each_result(trials, threads, |res| out.write(&res))
+}
+
+// Runs 'trials' like 'run_trials' but writes a summary of each run of 'repetitions' consecutive
+// trials, which should be repetitions of the same configuration as produced by 'Sweep'
+pub fn run_summaries<W: Write>(
+    trials: &[Trial],
+    repetitions: usize,
+    threads: NonZeroUsize,
+    out: &mut SummaryWriter<W>,
+) -> io::Result<()> {
+    let mut group = Vec::with_capacity(repetitions);
+    for_each_result(trials, threads, |res| {
+        group.push(res);
+        if group.len() == repetitions {
+            out.write(&GapSummary::new(&group))?;
+            group.clear();
+        }
+        Ok(())
+    })
+}
+
+// Runs 'trials' on 'threads' worker threads, passing the results to 'f' in the order of 'trials'
+fn for_each_result(
+    trials: &[Trial],
+    threads: NonZeroUsize,
+    mut f: impl FnMut(TrialResult) -> io::Result<()>,
+) -> io::Result<()> {
+    let next = AtomicUsize::new(0);
+    let (send, recv) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..threads.get().min(trials.len()) {
+            let send = send.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(trial) = trials.get(idx) else { break };
+                if send.send((idx, trial.run())).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(send);
+        // results arrive out of order, buffer them until all earlier trials have been written
+        let mut pending = BTreeMap::new();
+        let mut written = 0;
+        for (idx, res) in recv {
+            pending.insert(idx, res);
+            while let Some(res) = pending.remove(&written) {
+                f(res.map_err(io::Error::other)?)?;
+                written += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn test_thread_independent() {
+    let sweep = Sweep {
+        families: vec![Family::Random, Family::Ring],
+        ns: vec![20, 30],
+        ds: vec![3],
+        balls: vec![BallSpec::Linear(5)],
+        algorithms: vec![crate::spec::AlgorithmSpec::Greedy],
+        storage: crate::spec::StorageSpec::Relative,
+        repetitions: 4,
+        seed: 11,
+    };
+    let trials = sweep.trials().unwrap();
+    let run = |threads| {
+        let mut out = TrialWriter::new(vec![], crate::output::Format::Csv).unwrap();
+        run_trials(&trials, NonZeroUsize::new(threads).unwrap(), &mut out).unwrap();
+        // drop the wall time column which naturally differs between runs
+        let out = String::from_utf8(out.into_inner()).unwrap();
+        out.lines()
+            .map(|l| l.rsplit_once(',').unwrap().0.to_string())
+            .collect::<Vec<_>>()
+    };
+    let serial = run(1);
+    assert_eq!(serial.len(), trials.len() + 1);
+    assert_eq!(serial, run(3));
+    assert_eq!(serial, run(8));
+}
+
+#[test]
+fn test_summaries() {
+    let sweep = Sweep {
+        families: vec![],
+        ns: vec![],
+        ds: vec![],
+        balls: vec![BallSpec::Linear(10)],
+        algorithms: vec![crate::spec::AlgorithmSpec::Greedy],
+        storage: crate::spec::StorageSpec::Narrow,
+        repetitions: 5,
+        seed: 2,
+    };
+    let graphs: Vec<_> = ["graph6:C~", "graph6:Dhc"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let trials = sweep.trials_on(graphs);
+    assert_eq!(trials.len(), 10);
+    let mut out = SummaryWriter::new(vec![], crate::output::Format::Csv).unwrap();
+    run_summaries(&trials, 5, NonZeroUsize::new(2).unwrap(), &mut out).unwrap();
+    let out = String::from_utf8(out.into_inner()).unwrap();
+    let rows: Vec<Vec<&str>> = out
+        .lines()
+        .skip(1)
+        .map(|l| l.split(',').collect())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0][..9],
+        [
+            "0",
+            "graph6",
+            "graph6:C~",
+            "4",
+            "3",
+            "6",
+            "greedy",
+            "40",
+            "5"
+        ]
+    );
+    assert_eq!(
+        rows[1][..9],
+        [
+            "1",
+            "graph6",
+            "graph6:Dhc",
+            "5",
+            "2",
+            "5",
+            "greedy",
+            "50",
+            "5"
+        ]
+    );
+}
@@ -0,0 +1,107 @@
+use std::io::{self, Write};
+
+use crate::graph::Bin;
+use crate::output::{json_option, json_string, Format, FormatWriter};
+use crate::spec::{AlgorithmSpec, GraphSpec};
+
+use super::TrialResult;
+
+const COLUMNS: &str =
+    "index,family,graph,n,d,m,algorithm,balls,repetitions,average_gap,min_gap,max_gap";
+
+// The gaps of the repetitions of a single graph, algorithm and ball count
+#[derive(Clone, Debug)]
+pub struct GapSummary {
+    pub graph: GraphSpec,
+    pub algorithm: AlgorithmSpec,
+    pub n: usize,
+    pub d: Option<usize>,
+    pub m: usize,
+    pub balls: u64,
+    pub repetitions: usize,
+    pub average_gap: f64,
+    pub min_gap: Bin,
+    pub max_gap: Bin,
+}
+
+impl GapSummary {
+    // Summarises 'results', which must all be repetitions of the same configuration
+    pub fn new(results: &[TrialResult]) -> Self {
+        let first = results.first().expect("no results to summarise");
+        let gaps = results.iter().map(|res| res.gap);
+        GapSummary {
+            graph: first.trial.graph.clone(),
+            algorithm: first.trial.algorithm,
+            n: first.n,
+            d: first.d,
+            m: first.m,
+            balls: first.balls,
+            repetitions: results.len(),
+            average_gap: gaps.clone().sum::<Bin>() as f64 / results.len() as f64,
+            min_gap: gaps.clone().min().unwrap(),
+            max_gap: gaps.max().unwrap(),
+        }
+    }
+}
+
+// Writes one row (CSV) or object (JSON) per summary, numbering them from 0
+pub struct SummaryWriter<W: Write> {
+    inner: FormatWriter<W>,
+}
+
+impl<W: Write> SummaryWriter<W> {
+    pub fn new(out: W, format: Format) -> Result<Self, String> {
+        FormatWriter::tabular(out, format, "summaries").map(|inner| SummaryWriter { inner })
+    }
+
+    pub fn write(&mut self, summary: &GapSummary) -> io::Result<()> {
+        let index = self.inner.written();
+        let format = self.inner.format();
+        let out = self.inner.record(COLUMNS)?;
+        match format {
+            Format::Csv => writeln!(
+                out,
+                "{index},{},{},{},{},{},{},{},{},{},{},{}",
+                summary.graph.family(),
+                summary.graph,
+                summary.n,
+                summary.d.map_or(String::new(), |d| d.to_string()),
+                summary.m,
+                summary.algorithm,
+                summary.balls,
+                summary.repetitions,
+                summary.average_gap,
+                summary.min_gap,
+                summary.max_gap
+            )?,
+            Format::Json => write!(
+                out,
+                "{{\"index\": {index}, \"family\": {}, \"graph\": {}, \"n\": {}, \"d\": {}, \
+                     \"m\": {}, \"algorithm\": {}, \"balls\": {}, \"repetitions\": {}, \
+                     \"average_gap\": {}, \"min_gap\": {}, \"max_gap\": {}}}",
+                json_string(summary.graph.family()),
+                json_string(&summary.graph.to_string()),
+                summary.n,
+                json_option(summary.d),
+                summary.m,
+                json_string(&summary.algorithm.to_string()),
+                summary.balls,
+                summary.repetitions,
+                summary.average_gap,
+                summary.min_gap,
+                summary.max_gap
+            )?,
+            Format::Dot => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // Returns the underlying writer without writing any trailing output
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::rng::repetition_seed;
+use crate::spec::{AlgorithmSpec, GeneratorSpec, GraphSpec, StorageSpec};
+use crate::Error;
+
+use super::Trial;
+
+// A graph family whose parameters are filled in from a sweep over n and d
+// ring, full, random and star graphs have n vertices, the torus and grid are n x n and the
+// hypercube has dimension d
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Family {
+    Ring,
+    Torus,
+    Grid,
+    HyperCube,
+    Full,
+    Random,
+    Star,
+}
+
+impl Family {
+    // Fails unless balls can be thrown into the graph, see 'GraphSpec::check'
+    pub fn spec(self, n: usize, d: usize) -> Result<GraphSpec, String> {
+        let spec = match self {
+            Family::Ring => GraphSpec::Ring { n },
+            Family::Torus => GraphSpec::Torus { sides: vec![n, n] },
+            Family::Grid => GraphSpec::Grid { sides: vec![n, n] },
+            Family::HyperCube => GraphSpec::HyperCube {
+                d: d.try_into()
+                    .map_err(|_| format!("hypercube dimension {d} is too large"))?,
+            },
+            Family::Full => GraphSpec::Full { n },
+            Family::Random => GraphSpec::Random {
+                n,
+                d,
+                generator: GeneratorSpec::default(),
+            },
+            Family::Star => GraphSpec::Star { n },
+        };
+        spec.check()
+            .map_err(|err| format!("invalid graph '{spec}' in sweep: {err}"))?;
+        Ok(spec)
+    }
+}
+
+impl FromStr for Family {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ring" => Ok(Family::Ring),
+            "torus" => Ok(Family::Torus),
+            "grid" => Ok(Family::Grid),
+            "hypercube" => Ok(Family::HyperCube),
+            "full" => Ok(Family::Full),
+            "random" => Ok(Family::Random),
+            "star" => Ok(Family::Star),
+            _ => Err(format!("unknown graph family '{s}'")),
+        }
+    }
+}
+
+// Number of balls to throw, possibly relative to the number of vertices n of the graph
+// Written as eg. '1000', 'n', '10n' or 'n^2'
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BallSpec {
+    Fixed(u64),
+    Linear(u64),
+    Quadratic(u64),
+}
+
+impl BallSpec {
+    pub fn balls(&self, n: usize) -> Result<u64, Error> {
+        let balls = match *self {
+            BallSpec::Fixed(m) => Some(m),
+            BallSpec::Linear(k) => k.checked_mul(n as u64),
+            BallSpec::Quadratic(k) => k
+                .checked_mul(n as u64)
+                .and_then(|balls| balls.checked_mul(n as u64)),
+        };
+        balls.ok_or(Error::TooManyBalls { n })
+    }
+}
+
+impl FromStr for BallSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid ball count '{s}', expected eg. '1000', '10n' or 'n^2'");
+        let coefficient = |k: &str| {
+            if k.is_empty() {
+                Ok(1)
+            } else {
+                k.strip_suffix('*').unwrap_or(k).parse().map_err(|_| err())
+            }
+        };
+        if let Some(k) = s.strip_suffix("n^2").or_else(|| s.strip_suffix("n²")) {
+            Ok(BallSpec::Quadratic(coefficient(k)?))
+        } else if let Some(k) = s.strip_suffix('n') {
+            Ok(BallSpec::Linear(coefficient(k)?))
+        } else {
+            s.parse().map(BallSpec::Fixed).map_err(|_| err())
+        }
+    }
+}
+
+impl Display for BallSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BallSpec::Fixed(m) => write!(f, "{m}"),
+            BallSpec::Linear(1) => write!(f, "n"),
+            BallSpec::Linear(k) => write!(f, "{k}n"),
+            BallSpec::Quadratic(1) => write!(f, "n^2"),
+            BallSpec::Quadratic(k) => write!(f, "{k}n^2"),
+        }
+    }
+}
+
+// The cartesian product of graph families, parameters, ball counts and algorithms,
+// each configuration being repeated 'repetitions' times
+pub struct Sweep {
+    pub families: Vec<Family>,
+    pub ns: Vec<usize>,
+    pub ds: Vec<usize>,
+    pub balls: Vec<BallSpec>,
+    pub algorithms: Vec<AlgorithmSpec>,
+    pub storage: StorageSpec,
+    pub repetitions: usize,
+    pub seed: u64,
+}
+
+impl Sweep {
+    // Distinct graphs in the sweep, families that ignore n or d only appear once for each value
+    // of the parameters they use
+    pub fn graphs(&self) -> Result<Vec<GraphSpec>, String> {
+        let mut res = vec![];
+        for &family in &self.families {
+            for &n in &self.ns {
+                for &d in &self.ds {
+                    let spec = family.spec(n, d)?;
+                    if !res.contains(&spec) {
+                        res.push(spec)
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    // Every trial in the sweep, trial i is seeded with the i'th repetition seed of the sweep's seed
+    pub fn trials(&self) -> Result<Vec<Trial>, String> {
+        Ok(self.trials_on(self.graphs()?))
+    }
+
+    // Same as 'trials' but on the given graphs instead of those from the families, ns and ds
+    // The repetitions of each configuration are consecutive
+    pub fn trials_on(&self, graphs: Vec<GraphSpec>) -> Vec<Trial> {
+        let mut res = vec![];
+        for graph in graphs {
+            for &balls in &self.balls {
+                for &algorithm in &self.algorithms {
+                    for repetition in 0..self.repetitions {
+                        res.push(Trial {
+                            graph: graph.clone(),
+                            algorithm,
+                            balls,
+                            storage: self.storage,
+                            repetition,
+                            seed: repetition_seed(self.seed, res.len()),
+                        })
+                    }
+                }
+            }
+        }
+        res
+    }
+}
+
+#[test]
+fn test_ball_spec() {
+    for (s, balls) in [
+        ("1000", 1000),
+        ("n", 20),
+        ("10n", 200),
+        ("n^2", 400),
+        ("3n^2", 1200),
+    ] {
+        let spec: BallSpec = s.parse().unwrap();
+        assert_eq!(spec.balls(20), Ok(balls));
+        assert_eq!(spec.to_string(), s);
+    }
+    assert_eq!("n²".parse(), Ok(BallSpec::Quadratic(1)));
+    assert_eq!("10*n".parse(), Ok(BallSpec::Linear(10)));
+    assert!("m".parse::<BallSpec>().is_err());
+    assert_eq!(
+        BallSpec::Quadratic(1).balls(1 << 32),
+        Err(Error::TooManyBalls { n: 1 << 32 })
+    );
+    assert_eq!(
+        BallSpec::Linear(u64::MAX).balls(2),
+        Err(Error::TooManyBalls { n: 2 })
+    );
+}
+
+#[test]
+fn test_trials() {
+    let sweep = Sweep {
+        families: vec![Family::Ring, Family::Random],
+        ns: vec![10, 20],
+        ds: vec![3, 4],
+        balls: vec![BallSpec::Linear(1)],
+        algorithms: vec![AlgorithmSpec::Greedy],
+        storage: StorageSpec::Narrow,
+        repetitions: 3,
+        seed: 100,
+    };
+    // ring ignores d so only contributes 2 graphs
+    assert_eq!(sweep.graphs().unwrap().len(), 6);
+    let trials = sweep.trials().unwrap();
+    assert_eq!(trials.len(), 18);
+    // the first trial uses the seed itself, and sweeps with consecutive seeds share no trials
+    assert_eq!(trials[0].seed, 100);
+    let next = Sweep { seed: 101, ..sweep }.trials().unwrap();
+    let seeds: std::collections::HashSet<u64> =
+        trials.iter().chain(&next).map(|t| t.seed).collect();
+    assert_eq!(seeds.len(), 36);
+}
@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::graph::{Bin, BinGraph, BinStorage, Graph, RegionLoads};
+use crate::rng::seeded;
+use crate::spec::{AlgorithmSpec, BinGraphVisitor, GraphSpec, StorageSpec};
+use crate::Error;
+
+use super::BallSpec;
+
+// A single run of the balls and bins process, replayable from its seed alone
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trial {
+    pub graph: GraphSpec,
+    pub algorithm: AlgorithmSpec,
+    pub balls: BallSpec,
+    // storage does not affect the results, only the memory used and the maximum load supported
+    pub storage: StorageSpec,
+    pub repetition: usize,
+    pub seed: u64,
+}
+
+// Summary of the final state of a trial
+#[derive(Clone, Debug)]
+pub struct TrialResult {
+    pub trial: Trial,
+    pub n: usize,
+    // None for irregular graphs
+    pub d: Option<usize>,
+    pub m: usize,
+    pub balls: u64,
+    pub gap: Bin,
+    pub upper_gap: Bin,
+    pub max_load: Bin,
+    pub min_load: Bin,
+    // loads of the vertices not on the boundary, None if every vertex is on it
+    pub interior: Option<RegionLoads>,
+    // loads of the vertices on the boundary of graphs with one, see 'Layout'
+    pub boundary: Option<RegionLoads>,
+    // time taken to generate the graph and throw the balls
+    pub wall_time: Duration,
+}
+
+struct Run<'a> {
+    trial: &'a Trial,
+    start: Instant,
+}
+
+impl BinGraphVisitor for Run<'_> {
+    type Output = Result<TrialResult, Error>;
+
+    fn visit<G: Graph, S: BinStorage, R: Rng>(
+        self,
+        mut graph: BinGraph<G, S>,
+        rng: &mut R,
+    ) -> Self::Output {
+        let balls = self.trial.balls.balls(graph.n())?;
+        self.trial.algorithm.load_balance(&mut graph, balls, rng);
+        Ok(TrialResult {
+            trial: self.trial.clone(),
+            n: graph.n(),
+            d: graph.d(),
+            m: graph.m(),
+            balls,
+            gap: graph.gap(),
+            upper_gap: graph.upper_gap(),
+            max_load: graph.max_load(),
+            min_load: graph.min_load(),
+            interior: graph.region_loads(false),
+            boundary: graph.region_loads(true),
+            wall_time: self.start.elapsed(),
+        })
+    }
+}
+
+impl Trial {
+    pub fn run(&self) -> Result<TrialResult, Error> {
+        let start = Instant::now();
+        self.graph.build_bins(
+            self.storage,
+            &mut seeded(self.seed),
+            Run { trial: self, start },
+        )?
+    }
+}
+
+#[test]
+fn test_run() {
+    let trial = Trial {
+        graph: GraphSpec::Random {
+            n: 20,
+            d: 3,
+            generator: crate::spec::GeneratorSpec::default(),
+        },
+        algorithm: AlgorithmSpec::Greedy,
+        balls: BallSpec::Linear(10),
+        storage: StorageSpec::Narrow,
+        repetition: 0,
+        seed: 3,
+    };
+    let (res1, res2) = (trial.run().unwrap(), trial.run().unwrap());
+    assert_eq!((res1.n, res1.d, res1.m, res1.balls), (20, Some(3), 30, 200));
+    assert_eq!(res1.max_load - res1.min_load, res1.gap);
+    assert_eq!(
+        (res1.gap, res1.upper_gap, res1.max_load),
+        (res2.gap, res2.upper_gap, res2.max_load)
+    );
+}
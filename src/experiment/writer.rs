@@ -0,0 +1,96 @@
+use std::io::{self, Write};
+
+use crate::graph::{Bin, RegionLoads};
+use crate::output::{json_option, json_string, Format, FormatWriter};
+
+use super::TrialResult;
+
+const COLUMNS: &str = "family,graph,n,d,m,algorithm,balls,repetition,seed,gap,upper_gap,max_load,\
+                       min_load,boundary_vertices,interior_max_load,interior_min_load,\
+                       boundary_max_load,boundary_min_load,wall_time_s";
+
+// Writes one row (CSV) or object (JSON) per trial
+pub struct TrialWriter<W: Write> {
+    inner: FormatWriter<W>,
+}
+
+impl<W: Write> TrialWriter<W> {
+    pub fn new(out: W, format: Format) -> Result<Self, String> {
+        FormatWriter::tabular(out, format, "trial results").map(|inner| TrialWriter { inner })
+    }
+
+    pub fn write(&mut self, res: &TrialResult) -> io::Result<()> {
+        let format = self.inner.format();
+        let out = self.inner.record(COLUMNS)?;
+        let trial = &res.trial;
+        let wall_time = res.wall_time.as_secs_f64();
+        let boundary_vertices = res.boundary.map_or(0, |loads| loads.vertices);
+        let max_load = |loads: Option<RegionLoads>| loads.map(|loads| loads.max_load);
+        let min_load = |loads: Option<RegionLoads>| loads.map(|loads| loads.min_load);
+        let cell = |x: Option<Bin>| x.map_or(String::new(), |x| x.to_string());
+        match format {
+            Format::Csv => {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{boundary_vertices},{},{},{},{},{wall_time}",
+                    trial.graph.family(),
+                    trial.graph,
+                    res.n,
+                    res.d.map_or(String::new(), |d| d.to_string()),
+                    res.m,
+                    trial.algorithm,
+                    res.balls,
+                    trial.repetition,
+                    trial.seed,
+                    res.gap,
+                    res.upper_gap,
+                    res.max_load,
+                    res.min_load,
+                    cell(max_load(res.interior)),
+                    cell(min_load(res.interior)),
+                    cell(max_load(res.boundary)),
+                    cell(min_load(res.boundary))
+                )?
+            }
+            Format::Json => {
+                write!(
+                    out,
+                    "{{\"family\": {}, \"graph\": {}, \"n\": {}, \"d\": {}, \"m\": {}, \"algorithm\": {}, \
+                     \"balls\": {}, \"repetition\": {}, \"seed\": {}, \"gap\": {}, \
+                     \"upper_gap\": {}, \"max_load\": {}, \"min_load\": {}, \
+                     \"boundary_vertices\": {boundary_vertices}, \"interior_max_load\": {}, \
+                     \"interior_min_load\": {}, \"boundary_max_load\": {}, \
+                     \"boundary_min_load\": {}, \"wall_time_s\": {wall_time}}}",
+                    json_string(trial.graph.family()),
+                    json_string(&trial.graph.to_string()),
+                    res.n,
+                    json_option(res.d),
+                    res.m,
+                    json_string(&trial.algorithm.to_string()),
+                    res.balls,
+                    trial.repetition,
+                    trial.seed,
+                    res.gap,
+                    res.upper_gap,
+                    res.max_load,
+                    res.min_load,
+                    json_option(max_load(res.interior)),
+                    json_option(min_load(res.interior)),
+                    json_option(max_load(res.boundary)),
+                    json_option(min_load(res.boundary))
+                )?
+            }
+            Format::Dot => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // Returns the underlying writer without writing any trailing output
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
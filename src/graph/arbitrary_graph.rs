@@ -4,7 +4,8 @@ use std::vec::IntoIter;
 
 use rand::Rng;
 
-use crate::graph::Graph;
+use crate::graph::{Graph, McKayWormald, RegularGenerator};
+use crate::Error;
 
 pub struct ArbitraryGraph {
     d: usize,
@@ -29,8 +30,8 @@ impl Graph for ArbitraryGraph {
         self.data.len() / self.d
     }
 
-    fn d(&self) -> usize {
-        self.d
+    fn d(&self) -> Option<usize> {
+        Some(self.d)
     }
 
     fn as_idx(&self, v: Self::Vertex) -> usize {
@@ -38,7 +39,7 @@ impl Graph for ArbitraryGraph {
     }
 
     fn iter_vertices(&self) -> Self::VIter {
-        (0..self.n()).map(|x| Vertex(x))
+        (0..self.n()).map(Vertex)
     }
 
     fn iter_neighbours(&self, v: Vertex) -> Self::NIter {
@@ -52,27 +53,21 @@ impl Graph for ArbitraryGraph {
 
     fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
         let v = Vertex(rng.gen_range(0..self.n()));
-        let off = rng.gen_range(0..self.d());
+        let off = rng.gen_range(0..self.d);
         (v, self.neighbours(v)[off])
     }
 }
 
 impl ArbitraryGraph {
-    pub fn random(n: usize, d: usize) -> ArbitraryGraph {
-        let pairing = super::deg::deg(n, d, &mut rand::thread_rng());
-        let mut res = vec![vec![]; n];
-        pairing.cell_pairs().for_each(|(v1, v2)| {
-            res[v1].push(v2);
-            res[v2].push(v1);
-        });
-        let res = res
-            .into_iter()
-            .flat_map(|v| {
-                assert_eq!(v.len(), d);
-                v.into_iter().map(|v| Vertex(v))
-            })
-            .collect();
-        ArbitraryGraph { data: res, d }
+    // A uniformly random simple d-regular graph, see 'McKayWormald'
+    pub fn random(n: usize, d: usize, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error> {
+        McKayWormald.generate(n, d, rng)
+    }
+
+    // The graph where the neighbours of vertex v are the v'th run of d indexes in 'neighbours'
+    pub(super) fn from_neighbours(d: usize, neighbours: impl IntoIterator<Item = usize>) -> Self {
+        let data = neighbours.into_iter().map(Vertex).collect();
+        ArbitraryGraph { data, d }
     }
 
     fn neighbours(&self, v: Vertex) -> &[Vertex] {
@@ -82,7 +77,33 @@ impl ArbitraryGraph {
 
 #[test]
 fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
     for _ in 1..10 {
-        ArbitraryGraph::random(28, 3).validate()
+        ArbitraryGraph::random(28, 3, &mut rng)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid()
+    }
+}
+
+#[test]
+fn test_large() {
+    let mut rng = crate::rng::seeded(1);
+    let graph = ArbitraryGraph::random(100_000, 4, &mut rng).unwrap();
+    assert_eq!(graph.n(), 100_000);
+    graph.validate(&mut rng).assert_valid();
+}
+
+// The scale the generator is meant for, which takes about 20 seconds in a debug build so is left
+// out of the default run
+// Run with 'cargo test --release -- --ignored test_million'
+#[test]
+#[ignore]
+fn test_million() {
+    let mut rng = crate::rng::seeded(2);
+    for d in [3, 4, 10] {
+        let graph = ArbitraryGraph::random(1_000_000, d, &mut rng).unwrap();
+        assert_eq!((graph.n(), graph.d()), (1_000_000, Some(d)));
+        graph.validate(&mut rng).assert_valid();
     }
 }
@@ -0,0 +1,95 @@
+use std::iter::repeat_n;
+
+use super::Bin;
+
+// How a 'BinGraph' stores the number of balls in each bin
+pub trait BinStorage {
+    fn new(n: usize) -> Self;
+    fn get(&self, idx: usize) -> Bin;
+    // Adds a ball to bin 'idx', 'min' is the smallest load of any bin before the ball is added
+    fn increment(&mut self, idx: usize, min: Bin);
+}
+
+// A u32 per bin, enough for most experiments
+pub struct NarrowBins(Box<[u32]>);
+
+impl BinStorage for NarrowBins {
+    fn new(n: usize) -> Self {
+        NarrowBins(repeat_n(0, n).collect())
+    }
+
+    fn get(&self, idx: usize) -> Bin {
+        self.0[idx] as Bin
+    }
+
+    fn increment(&mut self, idx: usize, _: Bin) {
+        let bin = &mut self.0[idx];
+        *bin = bin
+            .checked_add(1)
+            .expect("bin overflowed u32, use WideBins for heavily loaded graphs");
+    }
+}
+
+// A u64 per bin, for when more than 2^32 balls may end up in a single bin
+pub struct WideBins(Box<[u64]>);
+
+impl BinStorage for WideBins {
+    fn new(n: usize) -> Self {
+        WideBins(repeat_n(0, n).collect())
+    }
+
+    fn get(&self, idx: usize) -> Bin {
+        self.0[idx]
+    }
+
+    fn increment(&mut self, idx: usize, _: Bin) {
+        self.0[idx] += 1
+    }
+}
+
+// A single byte per bin storing its load relative to a shared baseline
+// When a bin would overflow, the baseline is raised to the current minimum load, so this only
+// fails if the gap exceeds 255, which the graphical process makes very unlikely
+pub struct RelativeBins {
+    base: Bin,
+    offsets: Box<[u8]>,
+}
+
+impl BinStorage for RelativeBins {
+    fn new(n: usize) -> Self {
+        RelativeBins {
+            base: 0,
+            offsets: repeat_n(0, n).collect(),
+        }
+    }
+
+    fn get(&self, idx: usize) -> Bin {
+        self.base + self.offsets[idx] as Bin
+    }
+
+    fn increment(&mut self, idx: usize, min: Bin) {
+        if self.offsets[idx] == u8::MAX {
+            let shift = min - self.base;
+            assert!(shift > 0, "gap exceeded the 255 supported by RelativeBins");
+            // every offset is at least 'shift' since 'min' is the smallest load
+            self.offsets.iter_mut().for_each(|x| *x -= shift as u8);
+            self.base = min;
+        }
+        self.offsets[idx] += 1
+    }
+}
+
+#[test]
+fn test_relative() {
+    let mut bins = RelativeBins::new(3);
+    // keep the gap at most 2 while throwing far more than 255 balls into each bin
+    for i in 0..3000 {
+        let min = (0..3).map(|idx| bins.get(idx)).min().unwrap();
+        bins.increment(i % 3, min);
+    }
+    assert_eq!(
+        (0..3).map(|idx| bins.get(idx)).collect::<Vec<_>>(),
+        [1000; 3]
+    );
+    assert!(bins.base > 0);
+}
@@ -0,0 +1,145 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::Graph;
+use crate::Error;
+
+// The k-dimensional butterfly network, with a row for each of the 2^k words w of k bits and
+// k + 1 levels, joining (w, i) to (w, i + 1) and to (w ^ 2^i, i + 1)
+// Vertices on the first and last level have two neighbours and the others four
+// Vertex (w, i) has index i * 2^k + w
+pub struct ButterflyGraph {
+    k: u8,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct ButterflyVertex(usize);
+
+impl Display for ButterflyVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for ButterflyGraph {
+    type Vertex = ButterflyVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> ButterflyVertex>;
+    type NIter = smallvec::IntoIter<[ButterflyVertex; 4]>;
+
+    fn n(&self) -> usize {
+        (usize::from(self.k) + 1) << self.k
+    }
+
+    fn d(&self) -> Option<usize> {
+        match self.k {
+            0 => Some(0),
+            // a single level of crossings is a 4-cycle
+            1 => Some(2),
+            _ => None,
+        }
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        let (_, level) = self.row_level(v);
+        2 * (usize::from(level > 0) + usize::from(level < self.k))
+    }
+
+    fn m(&self) -> usize {
+        // two edges from every vertex on each level to the next
+        usize::from(self.k) << (self.k + 1)
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(ButterflyVertex)
+    }
+
+    fn iter_neighbours(&self, v: ButterflyVertex) -> Self::NIter {
+        let (row, level) = self.row_level(v);
+        let mut res = SmallVec::new();
+        if level > 0 {
+            res.push(self.vertex(row, level - 1));
+            res.push(self.vertex(row ^ (1 << (level - 1)), level - 1));
+        }
+        if level < self.k {
+            res.push(self.vertex(row, level + 1));
+            res.push(self.vertex(row ^ (1 << level), level + 1));
+        }
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        let ((v_row, v_level), (u_row, u_level)) = (self.row_level(v), self.row_level(u));
+        let level = v_level.min(u_level);
+        v_level.abs_diff(u_level) == 1 && (v_row ^ u_row) & !(1 << level) == 0
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // every vertex below the last level has two edges up
+        let row = rng.gen_range(0..1 << self.k);
+        let level = rng.gen_range(0..self.k);
+        let v = self.vertex(row, level);
+        let u = if rng.gen() {
+            self.vertex(row, level + 1)
+        } else {
+            self.vertex(row ^ (1 << level), level + 1)
+        };
+        if rng.gen() {
+            (v, u)
+        } else {
+            (u, v)
+        }
+    }
+}
+
+impl ButterflyGraph {
+    pub fn new(k: u8) -> Result<Self, Error> {
+        // 2 n = (k + 1) 2^(k + 1) fits in a usize, so m does too
+        if u32::from(k) + 1 >= usize::BITS || (usize::from(k) + 1).checked_mul(2 << k).is_none() {
+            Err(Error::TooManyVertices)
+        } else {
+            Ok(ButterflyGraph { k })
+        }
+    }
+
+    // The row and level of 'v'
+    pub fn row_level(&self, v: ButterflyVertex) -> (usize, u8) {
+        (v.0 % (1 << self.k), (v.0 >> self.k) as u8)
+    }
+
+    pub fn vertex(&self, row: usize, level: u8) -> ButterflyVertex {
+        ButterflyVertex((usize::from(level) << self.k) + row)
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for k in 0..8 {
+        ButterflyGraph::new(k)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(ButterflyGraph::new(58).err(), Some(Error::TooManyVertices));
+    assert_eq!(ButterflyGraph::new(63).err(), Some(Error::TooManyVertices));
+}
+
+#[test]
+fn test_structure() {
+    let graph = ButterflyGraph::new(3).unwrap();
+    assert_eq!((graph.n(), graph.m()), (32, 48));
+    let v = graph.vertex(0b101, 1);
+    let mut neighbours: Vec<_> = graph
+        .iter_neighbours(v)
+        .map(|u| graph.row_level(u))
+        .collect();
+    neighbours.sort();
+    assert_eq!(neighbours, [(0b100, 0), (0b101, 0), (0b101, 2), (0b111, 2)]);
+}
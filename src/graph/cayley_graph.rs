@@ -0,0 +1,296 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::regular::check_regular;
+use super::torus_graph::strides;
+use super::Graph;
+use crate::Error;
+
+// The Cayley graph of the product of cyclic groups Z_n1 x ... x Z_nk, with one side n_i per factor,
+// in which v and u are joined when u - v is in a symmetric connection set
+// With a single side this is the circulant graph, so 'RingGraph' is the one with connection set
+// {1, -1}, and the torus is the one with the unit vectors and their inverses
+// Each vertex is its index, see 'strides', and group elements are indexed the same way
+pub struct CayleyGraph {
+    sides: Box<[usize]>,
+    strides: Box<[usize]>,
+    n: usize,
+    // the indexes of the connection set, sorted
+    connection: Box<[usize]>,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct CayleyVertex(usize);
+
+impl Display for CayleyVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for CayleyGraph {
+    type Vertex = CayleyVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> CayleyVertex>;
+    type NIter = smallvec::IntoIter<[CayleyVertex; 8]>;
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn d(&self) -> Option<usize> {
+        Some(self.connection.len())
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n).map(CayleyVertex)
+    }
+
+    fn iter_neighbours(&self, v: CayleyVertex) -> Self::NIter {
+        self.connection
+            .iter()
+            .map(|&g| CayleyVertex(self.add(v.0, g)))
+            .collect::<SmallVec<_>>()
+            .into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        self.connection
+            .binary_search(&self.add(u.0, self.inverse(v.0)))
+            .is_ok()
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        let v = rng.gen_range(0..self.n);
+        let g = self.connection[rng.gen_range(0..self.connection.len())];
+        (CayleyVertex(v), CayleyVertex(self.add(v, g)))
+    }
+}
+
+impl CayleyGraph {
+    // The Cayley graph whose connection set is 'generators' and their inverses, each generator
+    // having one coordinate per side, taken modulo that side
+    pub fn new(sides: &[usize], generators: &[Vec<usize>]) -> Result<Self, Error> {
+        let (strides, n) = strides(sides)?;
+        let mut res = CayleyGraph {
+            sides: sides.into(),
+            strides,
+            n,
+            connection: Box::new([]),
+        };
+        let mut connection = vec![];
+        for generator in generators {
+            if generator.len() != sides.len() {
+                return Err(Error::DimensionMismatch {
+                    expected: sides.len(),
+                    found: generator.len(),
+                });
+            }
+            let g = generator
+                .iter()
+                .zip(sides.iter().zip(res.strides.iter()))
+                .map(|(&c, (&side, &stride))| c % side * stride)
+                .sum();
+            // the identity would join every vertex to itself
+            if g == 0 {
+                return Err(Error::SelfLoop { v: 0 });
+            }
+            connection.extend([g, res.inverse(g)]);
+        }
+        connection.sort_unstable();
+        connection.dedup();
+        res.connection = connection.into_boxed_slice();
+        Ok(res)
+    }
+
+    // The circulant graph on n vertices joining v to v + g and v - g for each g in 'generators'
+    pub fn circulant(n: usize, generators: &[usize]) -> Result<Self, Error> {
+        let generators: Vec<Vec<usize>> = generators.iter().map(|&g| vec![g]).collect();
+        CayleyGraph::new(&[n], &generators)
+    }
+
+    // A Cayley graph of degree d whose connection set is drawn at random, adding random
+    // elements with their inverses until it has d elements
+    // Elements which are their own inverse add one to the degree rather than two, so there is
+    // one for odd d exactly when the number of vertices is even
+    pub fn random(sides: &[usize], d: usize, rng: &mut impl Rng) -> Result<Self, Error> {
+        CayleyGraph::check_random(sides, d)?;
+        let mut res = CayleyGraph::new(sides, &[])?;
+        let involutions = res.involutions();
+        'retry: loop {
+            let mut connection = vec![];
+            let mut used_involutions = 0;
+            while connection.len() < d {
+                let g = rng.gen_range(1..res.n);
+                let inverse = res.inverse(g);
+                if connection.contains(&g) {
+                    continue;
+                }
+                if g == inverse {
+                    connection.push(g);
+                    used_involutions += 1;
+                } else if connection.len() + 1 < d {
+                    connection.extend([g, inverse]);
+                } else if used_involutions == involutions {
+                    // only an involution would complete the set, and there are none left
+                    continue 'retry;
+                }
+            }
+            connection.sort_unstable();
+            res.connection = connection.into_boxed_slice();
+            return Ok(res);
+        }
+    }
+
+    // Fails if 'random' would
+    pub fn check_random(sides: &[usize], d: usize) -> Result<(), Error> {
+        let (_, n) = strides(sides)?;
+        check_regular(n, d)
+    }
+
+    pub fn sides(&self) -> &[usize] {
+        &self.sides
+    }
+
+    // The connection set, each element given by its coordinates
+    pub fn connection_set(&self) -> Vec<Vec<usize>> {
+        self.connection
+            .iter()
+            .map(|&g| self.coordinates(CayleyVertex(g)))
+            .collect()
+    }
+
+    // The coordinates of 'v', one per side
+    pub fn coordinates(&self, v: CayleyVertex) -> Vec<usize> {
+        (0..self.sides.len())
+            .map(|dim| self.coordinate(v.0, dim))
+            .collect()
+    }
+
+    fn coordinate(&self, g: usize, dim: usize) -> usize {
+        g / self.strides[dim] % self.sides[dim]
+    }
+
+    fn add(&self, g: usize, h: usize) -> usize {
+        (0..self.sides.len())
+            .map(|dim| {
+                let (side, a, b) = (
+                    self.sides[dim],
+                    self.coordinate(g, dim),
+                    self.coordinate(h, dim),
+                );
+                // a + b might overflow for sides close to usize::MAX
+                let sum = if b >= side - a { a - (side - b) } else { a + b };
+                sum * self.strides[dim]
+            })
+            .sum()
+    }
+
+    fn inverse(&self, g: usize) -> usize {
+        (0..self.sides.len())
+            .map(|dim| {
+                (self.sides[dim] - self.coordinate(g, dim)) % self.sides[dim] * self.strides[dim]
+            })
+            .sum()
+    }
+
+    // The number of elements other than the identity which are their own inverse, those whose
+    // coordinates are all either 0 or half their side
+    fn involutions(&self) -> usize {
+        let even = self.sides.iter().filter(|&&side| side % 2 == 0).count();
+        (1 << even) - 1
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for (sides, generators) in [
+        (&[10][..], &[vec![1], vec![3]][..]),
+        (&[10], &[vec![5]]),
+        (&[10], &[vec![5], vec![2], vec![8]]),
+        (&[7], &[]),
+        (&[4, 6], &[vec![1, 0], vec![0, 3], vec![2, 3]]),
+        (&[2, 2, 2], &[vec![1, 0, 0], vec![0, 1, 1]]),
+        (&[3, 1, 5], &[vec![4, 7, 13]]),
+    ] {
+        CayleyGraph::new(sides, generators)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(
+        CayleyGraph::new(&[4, 6], &[vec![1]]).err(),
+        Some(Error::DimensionMismatch {
+            expected: 2,
+            found: 1
+        })
+    );
+    assert_eq!(
+        CayleyGraph::circulant(10, &[3, 20]).err(),
+        Some(Error::SelfLoop { v: 0 })
+    );
+    assert_eq!(CayleyGraph::new(&[0], &[]).err(), Some(Error::NoVertices));
+}
+
+#[test]
+fn test_structure() {
+    use super::{CsrGraph, HammingGraph, RingGraph, TorusGraph};
+
+    // generators are closed under inverses, 5 being its own inverse
+    let graph = CayleyGraph::circulant(10, &[1, 9, 5, 13]).unwrap();
+    assert_eq!(graph.connection_set(), [[1], [3], [5], [7], [9]]);
+    assert_eq!(graph.d(), Some(5));
+    for n in 2..8 {
+        assert_eq!(
+            CsrGraph::from_graph(&CayleyGraph::circulant(n, &[1]).unwrap()),
+            CsrGraph::from_graph(&RingGraph::new(n).unwrap())
+        );
+    }
+    let graph = CayleyGraph::new(&[3, 4, 5], &[vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]]);
+    assert_eq!(
+        CsrGraph::from_graph(&graph.unwrap()),
+        CsrGraph::from_graph(&TorusGraph::new(&[3, 4, 5]).unwrap())
+    );
+    // H(2, 3) joins words differing in one letter, by any non-zero amount
+    let graph = CayleyGraph::new(&[3, 3], &[vec![1, 0], vec![0, 1]]);
+    assert_eq!(
+        CsrGraph::from_graph(&graph.unwrap()),
+        CsrGraph::from_graph(&HammingGraph::new(2, 3).unwrap())
+    );
+}
+
+#[test]
+fn test_random() {
+    let mut rng = crate::rng::seeded(0);
+    for (sides, d) in [
+        (&[10][..], 3),
+        (&[10], 9),
+        (&[4], 2),
+        (&[11], 4),
+        (&[4, 6], 5),
+        (&[2, 2, 2], 7),
+        (&[3, 5, 7], 10),
+        (&[1000], 1),
+    ] {
+        for _ in 0..10 {
+            let graph = CayleyGraph::random(sides, d, &mut rng).unwrap();
+            assert_eq!(graph.d(), Some(d));
+            graph.validate(&mut rng).assert_valid();
+        }
+    }
+    assert_eq!(
+        CayleyGraph::random(&[3, 5], 3, &mut rng).err(),
+        Some(Error::OddDegreeSum { n: 15, d: 3 })
+    );
+    assert_eq!(
+        CayleyGraph::random(&[4], 4, &mut rng).err(),
+        Some(Error::DegreeTooLarge { n: 4, d: 4 })
+    );
+}
@@ -0,0 +1,149 @@
+use rand::Rng;
+
+use super::Graph;
+use crate::Error;
+
+// The complement of a graph, joining exactly the distinct vertices it does not
+// The complement of a sparse graph is dense, so like 'FullGraph' listing the neighbours of a
+// vertex, and so picking a random edge, takes O(n) calls to 'has_edge'
+pub struct ComplementGraph<G: Graph> {
+    graph: G,
+    // the vertices of 'graph', in the order of 'iter_vertices'
+    vertices: Box<[G::Vertex]>,
+    // offsets[i] is the sum of the degrees in the complement of the vertices before vertices[i]
+    offsets: Box<[usize]>,
+}
+
+impl<G: Graph> Graph for ComplementGraph<G> {
+    type Vertex = G::Vertex;
+    type VIter = G::VIter;
+    type NIter = std::vec::IntoIter<G::Vertex>;
+
+    fn n(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn d(&self) -> Option<usize> {
+        Some(self.n() - 1 - self.graph.d()?)
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        self.n() - 1 - self.graph.degree(v)
+    }
+
+    fn m(&self) -> usize {
+        self.offsets[self.vertices.len()] / 2
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        self.graph.as_idx(v)
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        self.graph.iter_vertices()
+    }
+
+    fn iter_neighbours(&self, v: Self::Vertex) -> Self::NIter {
+        let res: Vec<_> = self
+            .vertices
+            .iter()
+            .copied()
+            .filter(|&u| self.has_edge(v, u))
+            .collect();
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        v != u && !self.graph.has_edge(v, u)
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // each edge appears once from each end, numbered by the offset of the first end and
+        // the position of the second among its neighbours
+        let idx = rng.gen_range(0..2 * self.m());
+        let i = self.offsets.partition_point(|&off| off <= idx) - 1;
+        let v = self.vertices[i];
+        let u = self.iter_neighbours(v).nth(idx - self.offsets[i]).unwrap();
+        (v, u)
+    }
+}
+
+impl<G: Graph> ComplementGraph<G> {
+    pub fn new(graph: G) -> Result<Self, Error> {
+        let vertices: Box<[_]> = graph.iter_vertices().collect();
+        let n = vertices.len();
+        if n == 0 {
+            return Err(Error::NoVertices);
+        }
+        n.checked_mul(n - 1).ok_or(Error::TooManyVertices)?;
+        let mut offsets = Vec::with_capacity(n + 1);
+        offsets.push(0);
+        for &v in vertices.iter() {
+            offsets.push(offsets.last().unwrap() + n - 1 - graph.degree(v));
+        }
+        Ok(ComplementGraph {
+            graph,
+            vertices,
+            offsets: offsets.into_boxed_slice(),
+        })
+    }
+
+    // The graph this is the complement of
+    pub fn original(&self) -> &G {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+use super::{FullGraph, GridGraph, RingGraph, StarGraph};
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for n in 1..8 {
+        ComplementGraph::new(RingGraph::new(n).unwrap())
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+        ComplementGraph::new(StarGraph::new(n).unwrap())
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    ComplementGraph::new(GridGraph::new(&[3, 4]).unwrap())
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+}
+
+#[test]
+fn test_structure() {
+    // the complement of a 5-cycle is another 5-cycle
+    let graph = ComplementGraph::new(RingGraph::new(5).unwrap()).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (5, Some(2), 5));
+    // the complement of a complete graph has no edges, and that of the star is complete but for
+    // the centre
+    let graph = ComplementGraph::new(FullGraph::new(6).unwrap()).unwrap();
+    assert_eq!((graph.d(), graph.m()), (Some(0), 0));
+    let graph = ComplementGraph::new(StarGraph::new(6).unwrap()).unwrap();
+    assert_eq!((graph.d(), graph.m()), (None, 10));
+    let degrees: Vec<usize> = graph.iter_vertices().map(|v| graph.degree(v)).collect();
+    assert_eq!(degrees, [0, 4, 4, 4, 4, 4]);
+    // the complement of the complement is the graph
+    let ring = RingGraph::new(6).unwrap();
+    let graph = ComplementGraph::new(ComplementGraph::new(RingGraph::new(6).unwrap()).unwrap());
+    let graph = graph.unwrap();
+    for v in ring.iter_vertices() {
+        for u in ring.iter_vertices() {
+            assert_eq!(graph.has_edge(v, u), ring.has_edge(v, u));
+        }
+    }
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    // the complement of a path has vertices of different degrees
+    let mut rng = crate::rng::seeded(1);
+    let graph = ComplementGraph::new(GridGraph::new(&[6]).unwrap()).unwrap();
+    super::validation::assert_random_edge_uniform(&graph, &mut rng);
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::graph::Graph;
+use crate::Error;
+
+// A simple graph of arbitrary shape stored in compressed sparse row form
+// The neighbours of vertex i are 'targets[offsets[i]..offsets[i + 1]]', sorted by index
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsrGraph {
+    offsets: Box<[usize]>,
+    // shared with the neighbour iterators, so listing neighbours does not copy them
+    targets: Arc<[CsrVertex]>,
+    d: Option<usize>,
+    // the label each vertex had in the edge list it was read from
+    labels: Box<[u64]>,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct CsrVertex(usize);
+
+impl Display for CsrVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+// The neighbours of a vertex of a 'CsrGraph', the indices of its slice of 'targets'
+pub struct CsrNeighbours {
+    targets: Arc<[CsrVertex]>,
+    range: Range<usize>,
+}
+
+impl Iterator for CsrNeighbours {
+    type Item = CsrVertex;
+
+    fn next(&mut self) -> Option<CsrVertex> {
+        self.range.next().map(|i| self.targets[i])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CsrNeighbours {}
+
+impl Graph for CsrGraph {
+    type Vertex = CsrVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> CsrVertex>;
+    type NIter = CsrNeighbours;
+
+    fn n(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn d(&self) -> Option<usize> {
+        self.d
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        self.offsets[v.0 + 1] - self.offsets[v.0]
+    }
+
+    fn m(&self) -> usize {
+        self.targets.len() / 2
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(CsrVertex)
+    }
+
+    fn iter_neighbours(&self, v: CsrVertex) -> Self::NIter {
+        CsrNeighbours {
+            targets: Arc::clone(&self.targets),
+            range: self.offsets[v.0]..self.offsets[v.0 + 1],
+        }
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        self.neighbours(v).binary_search(&u).is_ok()
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // each edge appears once in each direction in 'targets'
+        let idx = rng.gen_range(0..self.targets.len());
+        let v = self.offsets.partition_point(|&off| off <= idx) - 1;
+        (CsrVertex(v), self.targets[idx])
+    }
+}
+
+impl CsrGraph {
+    // Builds the graph with vertices 0..n and the undirected 'edges'
+    // Fails if any edge is a self loop, is repeated or has an endpoint that is not a vertex
+    pub fn from_edges(n: usize, edges: &[(usize, usize)]) -> Result<Self, Error> {
+        let mut degrees = vec![0; n];
+        for &(u, v) in edges {
+            if u >= n || v >= n {
+                return Err(Error::EdgeOutOfRange { u, v, n });
+            } else if u == v {
+                return Err(Error::SelfLoop { v });
+            }
+            degrees[u] += 1;
+            degrees[v] += 1;
+        }
+        let mut offsets = Vec::with_capacity(n + 1);
+        offsets.push(0);
+        for degree in &degrees {
+            offsets.push(offsets.last().unwrap() + degree);
+        }
+        let mut fill = offsets[..n].to_vec();
+        let mut targets = vec![CsrVertex(0); 2 * edges.len()];
+        for &(u, v) in edges {
+            targets[fill[u]] = CsrVertex(v);
+            fill[u] += 1;
+            targets[fill[v]] = CsrVertex(u);
+            fill[v] += 1;
+        }
+        for u in 0..n {
+            let neighbours = &mut targets[offsets[u]..offsets[u + 1]];
+            neighbours.sort_unstable();
+            if let Some(w) = neighbours.windows(2).find(|w| w[0] == w[1]) {
+                return Err(Error::RepeatedEdge { u, v: w[0].0 });
+            }
+        }
+        let d = match degrees.split_first() {
+            Some((&d, rest)) if rest.iter().all(|&x| x == d) => Some(d),
+            Some(_) => None,
+            None => Some(0),
+        };
+        Ok(CsrGraph {
+            offsets: offsets.into_boxed_slice(),
+            targets: targets.into(),
+            d,
+            labels: (0..n as u64).collect(),
+        })
+    }
+
+    // Copies any graph into CSR form, keeping the vertex indexes of 'graph'
+    pub fn from_graph<G: Graph>(graph: &G) -> Self {
+        let edges: Vec<(usize, usize)> = graph
+            .iter_vertices()
+            .flat_map(|v| {
+                let v_idx = graph.as_idx(v);
+                graph
+                    .iter_neighbours(v)
+                    .map(move |u| (v_idx, graph.as_idx(u)))
+                    .filter(|&(v_idx, u_idx)| v_idx < u_idx)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        CsrGraph::from_edges(graph.n(), &edges).expect("graph was not simple")
+    }
+
+    // Reads a whitespace separated list of edges, one per line, between non-negative integer
+    // labels, lines starting with '#' or '%' are comments
+    // Vertices are numbered in increasing order of their labels
+    pub fn read_edge_list(input: impl BufRead) -> io::Result<Self> {
+        let invalid = |line: usize, msg: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {line}: {msg}"))
+        };
+        let mut edges = vec![];
+        for (line_idx, line) in input.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+                continue;
+            }
+            let labels: Vec<&str> = line.split_whitespace().collect();
+            let &[u, v] = &labels[..] else {
+                return Err(invalid(
+                    line_idx + 1,
+                    format!("expected 2 vertices in '{line}'"),
+                ));
+            };
+            let parse = |x: &str| {
+                x.parse::<u64>()
+                    .map_err(|_| invalid(line_idx + 1, format!("invalid vertex '{x}'")))
+            };
+            edges.push((parse(u)?, parse(v)?));
+        }
+        let mut labels: Vec<u64> = edges.iter().flat_map(|&(u, v)| [u, v]).collect();
+        labels.sort_unstable();
+        labels.dedup();
+        let index: HashMap<u64, usize> = labels.iter().enumerate().map(|(i, &l)| (l, i)).collect();
+        let edges: Vec<(usize, usize)> = edges.iter().map(|(u, v)| (index[u], index[v])).collect();
+        let mut res = CsrGraph::from_edges(labels.len(), &edges)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        res.labels = labels.into_boxed_slice();
+        Ok(res)
+    }
+
+    // Writes the graph as an edge list readable by 'read_edge_list', using the original labels
+    pub fn write_edge_list(&self, mut out: impl Write) -> io::Result<()> {
+        for v in self.iter_vertices() {
+            for &u in self.neighbours(v).iter().filter(|u| v.0 < u.0) {
+                writeln!(out, "{} {}", self.label(v), self.label(u))?;
+            }
+        }
+        out.flush()
+    }
+
+    // The largest connected component, the first of the largest if there are several, with the
+    // order of its vertices and their labels kept, and the indexes its vertices had in this graph
+    pub fn giant_component(&self) -> (CsrGraph, Box<[usize]>) {
+        let n = self.n();
+        let mut component = vec![usize::MAX; n];
+        let (mut giant, mut giant_size) = (0, 0);
+        let mut stack = vec![];
+        for start in 0..n {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            component[start] = start;
+            stack.push(CsrVertex(start));
+            let mut size = 0;
+            while let Some(v) = stack.pop() {
+                size += 1;
+                for &u in self.neighbours(v) {
+                    if component[u.0] == usize::MAX {
+                        component[u.0] = start;
+                        stack.push(u);
+                    }
+                }
+            }
+            if size > giant_size {
+                (giant, giant_size) = (start, size);
+            }
+        }
+        let kept: Vec<usize> = (0..n).filter(|&v| component[v] == giant).collect();
+        let mut index = vec![0; n];
+        for (i, &v) in kept.iter().enumerate() {
+            index[v] = i;
+        }
+        let edges: Vec<(usize, usize)> = kept
+            .iter()
+            .flat_map(|&v| {
+                let index = &index;
+                self.neighbours(CsrVertex(v))
+                    .iter()
+                    .filter(move |u| v < u.0)
+                    .map(move |u| (index[v], index[u.0]))
+            })
+            .collect();
+        let mut res = CsrGraph::from_edges(kept.len(), &edges).expect("graph was not simple");
+        res.labels = kept.iter().map(|&v| self.labels[v]).collect();
+        (res, kept.into())
+    }
+
+    // The label 'v' had in the edge list the graph was read from, or its index otherwise
+    pub fn label(&self, v: CsrVertex) -> u64 {
+        self.labels[v.0]
+    }
+
+    fn neighbours(&self, v: CsrVertex) -> &[CsrVertex] {
+        &self.targets[self.offsets[v.0]..self.offsets[v.0 + 1]]
+    }
+}
+
+#[test]
+fn test_valid() {
+    use super::{StarGraph, TorusGraph};
+
+    let mut rng = crate::rng::seeded(0);
+    CsrGraph::from_edges(0, &[])
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+    CsrGraph::from_edges(4, &[(0, 1), (1, 2), (2, 0), (2, 3)])
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+    CsrGraph::from_graph(&StarGraph::new(6).unwrap())
+        .validate(&mut rng)
+        .assert_valid();
+    let torus = CsrGraph::from_graph(&TorusGraph::new(&[4, 4]).unwrap());
+    assert_eq!((torus.d(), torus.m()), (Some(4), 32));
+    torus.validate(&mut rng).assert_valid();
+}
+
+#[test]
+fn test_not_simple() {
+    assert_eq!(
+        CsrGraph::from_edges(3, &[(0, 1), (1, 1)]).err(),
+        Some(Error::SelfLoop { v: 1 })
+    );
+    assert_eq!(
+        CsrGraph::from_edges(3, &[(0, 1), (1, 0)]).err(),
+        Some(Error::RepeatedEdge { u: 0, v: 1 })
+    );
+    assert_eq!(
+        CsrGraph::from_edges(3, &[(0, 3)]).err(),
+        Some(Error::EdgeOutOfRange { u: 0, v: 3, n: 3 })
+    );
+}
+
+#[test]
+fn test_edge_list() {
+    let input = "# a path with sparse labels\n10 20\n\n20 7\n% comment\n7 1000\n";
+    let graph = CsrGraph::read_edge_list(input.as_bytes()).unwrap();
+    assert_eq!((graph.n(), graph.m(), graph.d()), (4, 3, None));
+    let labels: Vec<u64> = graph.iter_vertices().map(|v| graph.label(v)).collect();
+    assert_eq!(labels, [7, 10, 20, 1000]);
+    let mut out = vec![];
+    graph.write_edge_list(&mut out).unwrap();
+    assert_eq!(CsrGraph::read_edge_list(&out[..]).unwrap(), graph);
+
+    assert!(CsrGraph::read_edge_list("1 2\n2 1\n".as_bytes()).is_err());
+    assert!(CsrGraph::read_edge_list("1 2 3\n".as_bytes()).is_err());
+    assert!(CsrGraph::read_edge_list("1 x\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    // a star plus an edge between two leaves, so a random vertex then random neighbour is biased
+    let graph = CsrGraph::from_edges(5, &[(0, 1), (0, 2), (0, 3), (0, 4), (3, 4)]).unwrap();
+    let mut rng = crate::rng::seeded(1);
+    let mut counts = HashMap::new();
+    for _ in 0..50000 {
+        let (u, v) = graph.random_edge(&mut rng);
+        *counts.entry((u.0.min(v.0), u.0.max(v.0))).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 5);
+    assert!(
+        counts.values().all(|&c| (9000..11000).contains(&c)),
+        "{counts:?}"
+    );
+}
+
+#[test]
+fn test_giant_component() {
+    // a path of three, an edge and an isolated vertex, with the path's vertices spread out
+    let graph = CsrGraph::from_edges(7, &[(0, 5), (5, 3), (1, 2), (6, 4)]).unwrap();
+    let (giant, kept) = graph.giant_component();
+    assert_eq!((giant.n(), giant.m()), (3, 2));
+    assert_eq!(*kept, [0, 3, 5]);
+    let labels: Vec<u64> = giant.iter_vertices().map(|v| giant.label(v)).collect();
+    assert_eq!(labels, [0, 3, 5]);
+    assert!(giant.has_edge(CsrVertex(0), CsrVertex(2)));
+    assert!(giant.has_edge(CsrVertex(1), CsrVertex(2)));
+    // a connected graph is its own giant component
+    let input = "10 20\n20 7\n";
+    let graph = CsrGraph::read_edge_list(input.as_bytes()).unwrap();
+    assert_eq!(graph.giant_component().0, graph);
+}
@@ -0,0 +1,142 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::ring_graph::{calc_d, RingVertex};
+use super::{Graph, RingGraph};
+use crate::Error;
+
+// The k-dimensional cube-connected cycles, the hypercube with each vertex w replaced by a ring of
+// k vertices (w, i), with (w, i) also joined to (w ^ 2^i, i) across dimension i
+// It is 3-regular for k >= 3, smaller rings having fewer edges as in 'RingGraph'
+// Vertex (w, i) has index w * k + i
+pub struct CubeConnectedCyclesGraph {
+    k: u8,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct CubeConnectedCyclesVertex(usize);
+
+impl Display for CubeConnectedCyclesVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for CubeConnectedCyclesGraph {
+    type Vertex = CubeConnectedCyclesVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> CubeConnectedCyclesVertex>;
+    type NIter = smallvec::IntoIter<[CubeConnectedCyclesVertex; 3]>;
+
+    fn n(&self) -> usize {
+        usize::from(self.k) << self.k
+    }
+
+    fn d(&self) -> Option<usize> {
+        Some(calc_d(self.k.into()) + 1)
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(CubeConnectedCyclesVertex)
+    }
+
+    fn iter_neighbours(&self, v: CubeConnectedCyclesVertex) -> Self::NIter {
+        let (word, i) = self.word_position(v);
+        let mut res: SmallVec<_> = self
+            .ring()
+            .iter_neighbours(RingVertex(i))
+            .map(|j| self.vertex(word, j.0))
+            .collect();
+        res.push(self.vertex(word ^ (1 << i), i));
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        let ((v_word, i), (u_word, j)) = (self.word_position(v), self.word_position(u));
+        // a ring of one vertex would have a loop
+        if v == u {
+            false
+        } else if v_word == u_word {
+            self.ring().has_edge(RingVertex(i), RingVertex(j))
+        } else {
+            i == j && v_word ^ u_word == 1 << i
+        }
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // the graph is regular, so pick a random one of the d neighbours
+        let v = CubeConnectedCyclesVertex(rng.gen_range(0..self.n()));
+        let u = self
+            .iter_neighbours(v)
+            .nth(rng.gen_range(0..self.d().unwrap()))
+            .unwrap();
+        (v, u)
+    }
+}
+
+impl CubeConnectedCyclesGraph {
+    pub fn new(k: u8) -> Result<Self, Error> {
+        if k == 0 {
+            return Err(Error::NoVertices);
+        }
+        if u32::from(k) >= usize::BITS || usize::from(k).checked_mul(1 << k).is_none() {
+            Err(Error::TooManyVertices)
+        } else {
+            Ok(CubeConnectedCyclesGraph { k })
+        }
+    }
+
+    // The hypercube vertex of 'v' and its position in the ring replacing it
+    pub fn word_position(&self, v: CubeConnectedCyclesVertex) -> (usize, usize) {
+        let k = usize::from(self.k);
+        (v.0 / k, v.0 % k)
+    }
+
+    pub fn vertex(&self, word: usize, position: usize) -> CubeConnectedCyclesVertex {
+        CubeConnectedCyclesVertex(word * usize::from(self.k) + position)
+    }
+
+    fn ring(&self) -> RingGraph {
+        RingGraph { n: self.k.into() }
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for k in 1..8 {
+        CubeConnectedCyclesGraph::new(k)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(
+        CubeConnectedCyclesGraph::new(0).err(),
+        Some(Error::NoVertices)
+    );
+    assert_eq!(
+        CubeConnectedCyclesGraph::new(60).err(),
+        Some(Error::TooManyVertices)
+    );
+}
+
+#[test]
+fn test_structure() {
+    let graph = CubeConnectedCyclesGraph::new(3).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (24, Some(3), 36));
+    let mut neighbours: Vec<_> = graph
+        .iter_neighbours(graph.vertex(0b110, 1))
+        .map(|u| graph.word_position(u))
+        .collect();
+    neighbours.sort();
+    assert_eq!(neighbours, [(0b100, 1), (0b110, 0), (0b110, 2)]);
+    // a single dimension is a single edge
+    let graph = CubeConnectedCyclesGraph::new(1).unwrap();
+    assert_eq!((graph.n(), graph.d()), (2, Some(1)));
+}
@@ -0,0 +1,161 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::Graph;
+use crate::Error;
+
+// The undirected de Bruijn graph on the words of length k over an alphabet of size q, joining
+// each word to those obtained by shifting it one letter left or right and filling the gap
+// The directed de Bruijn graph has an arc from each word to the q words it can shift into, so
+// here loops, at words of a single letter, are dropped and the two arcs between words like
+// 0101 and 1010 become one edge, so most vertices have 2q neighbours but some fewer
+// Each vertex is its index, whose digits in base q are the letters of the word, so shifting left
+// is 'v * q + x mod q^k' and shifting right is 'v / q + x * q^(k - 1)'
+pub struct DeBruijnGraph {
+    q: usize,
+    k: usize,
+    n: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct DeBruijnVertex(usize);
+
+impl Display for DeBruijnVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for DeBruijnGraph {
+    type Vertex = DeBruijnVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> DeBruijnVertex>;
+    type NIter = smallvec::IntoIter<[DeBruijnVertex; 4]>;
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn d(&self) -> Option<usize> {
+        // words of length 1 form the complete graph, and otherwise words of one repeated letter
+        // have fewer neighbours than the rest
+        match (self.q, self.k) {
+            (q, 1) => Some(q - 1),
+            _ => None,
+        }
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        self.iter_neighbours(v).len()
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n).map(DeBruijnVertex)
+    }
+
+    fn iter_neighbours(&self, v: DeBruijnVertex) -> Self::NIter {
+        let mut res = SmallVec::new();
+        for u in (0..self.q).flat_map(|x| [self.shift_left(v, x), self.shift_right(v, x)]) {
+            if u != v && !res.contains(&u) {
+                res.push(u);
+            }
+        }
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        v != u && (self.has_arc(v, u) || self.has_arc(u, v))
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // a random arc is uniform over the arcs, so rejecting loops and keeping an edge made of
+        // two arcs with probability 1 / 2 leaves each edge equally likely
+        loop {
+            let v = DeBruijnVertex(rng.gen_range(0..self.n));
+            let u = self.shift_left(v, rng.gen_range(0..self.q));
+            if u != v && (!self.has_arc(u, v) || rng.gen()) {
+                return if rng.gen() { (v, u) } else { (u, v) };
+            }
+        }
+    }
+}
+
+impl DeBruijnGraph {
+    pub fn new(q: usize, k: usize) -> Result<Self, Error> {
+        let mut n: usize = 1;
+        for _ in 0..k {
+            n = n.checked_mul(q).ok_or(Error::TooManyVertices)?;
+        }
+        match n {
+            0 => Err(Error::NoVertices),
+            // a single word, of one letter or none, only shifts into itself
+            1 => Err(Error::NoEdges),
+            _ => Ok(DeBruijnGraph { q, k, n }),
+        }
+    }
+
+    // Drops the first letter of 'v' and appends 'x'
+    fn shift_left(&self, v: DeBruijnVertex, x: usize) -> DeBruijnVertex {
+        DeBruijnVertex(v.0 % (self.n / self.q) * self.q + x)
+    }
+
+    // Drops the last letter of 'v' and prepends 'x'
+    fn shift_right(&self, v: DeBruijnVertex, x: usize) -> DeBruijnVertex {
+        DeBruijnVertex(v.0 / self.q + x * (self.n / self.q))
+    }
+
+    // Whether 'u' is a left shift of 'v', so there is an arc from 'v' to 'u'
+    fn has_arc(&self, v: DeBruijnVertex, u: DeBruijnVertex) -> bool {
+        v.0 % (self.n / self.q) == u.0 / self.q
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for (q, k) in [(2, 1), (2, 2), (2, 3), (2, 6), (3, 1), (3, 4), (5, 3)] {
+        DeBruijnGraph::new(q, k)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(DeBruijnGraph::new(0, 3).err(), Some(Error::NoVertices));
+    assert_eq!(DeBruijnGraph::new(1, 4).err(), Some(Error::NoEdges));
+    assert_eq!(DeBruijnGraph::new(4, 0).err(), Some(Error::NoEdges));
+    assert_eq!(
+        DeBruijnGraph::new(2, 64).err(),
+        Some(Error::TooManyVertices)
+    );
+}
+
+#[test]
+fn test_structure() {
+    let graph = DeBruijnGraph::new(2, 3).unwrap();
+    let neighbours = |v| {
+        let mut res: Vec<usize> = graph
+            .iter_neighbours(DeBruijnVertex(v))
+            .map(|u| u.0)
+            .collect();
+        res.sort();
+        res
+    };
+    // 011 shifts left into 110 and 111 and right into 001 and 101
+    assert_eq!(neighbours(0b011), [0b001, 0b101, 0b110, 0b111]);
+    // 000 only shifts into itself and 001 or 100
+    assert_eq!(neighbours(0b000), [0b001, 0b100]);
+    // 010 shifts left into 101 and right into 101 too
+    assert_eq!(neighbours(0b010), [0b001, 0b100, 0b101]);
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    let graph = DeBruijnGraph::new(2, 3).unwrap();
+    let mut rng = crate::rng::seeded(1);
+    super::validation::assert_random_edge_uniform(&graph, &mut rng);
+}
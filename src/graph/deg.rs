@@ -1,46 +1,56 @@
 // Algorithm based on https://doi.org/10.1016/0196-6774(90)90029-E
-use std::iter::repeat_with;
-use std::mem::swap;
+use std::ops::Range;
 
 use itertools::Itertools;
 use rand::{seq::SliceRandom, Rng};
 
-type SmallVec = smallvec::SmallVec<[usize; 2]>;
+type Cells = smallvec::SmallVec<[usize; 8]>;
 
+// n cells of d points each, with the points paired up
+// The points of cell c are c * d..(c + 1) * d, so memory is O(nd) and the number of pairs between
+// two cells is found by scanning the d points of one of them
 pub(super) struct Pairing {
     d: usize,
-    pairs: Box<[[usize; 2]]>,
-    //m/2 length list of pairs of points
-    edges: Box<[SmallVec]>, // n x n table listing the indexes in 'map' of edges between a cell x cell pair
+    // the point each point is paired with
+    partner: Box<[usize]>,
 }
 
 impl Pairing {
+    fn random(n: usize, d: usize, rng: &mut impl Rng) -> Pairing {
+        assert_eq!((n * d) % 2, 0);
+        let mut permutation: Box<[usize]> = (0..n * d).collect();
+        permutation.shuffle(rng);
+        let mut partner = vec![0; n * d].into_boxed_slice();
+        for pair in permutation.chunks_exact(2) {
+            partner[pair[0]] = pair[1];
+            partner[pair[1]] = pair[0];
+        }
+        Pairing { d, partner }
+    }
+
     fn n(&self) -> usize {
-        self.pairs.len() * 2 / self.d
+        self.partner.len() / self.d
     }
 
-    fn same_cell(&self, p1: usize, p2: usize) -> bool {
-        p1 / self.d == p2 / self.d
+    fn cell(&self, p: usize) -> usize {
+        p / self.d
     }
 
-    // returns the index into `edges` for edges between p1s and p2s cell
-    fn edges_idx(&self, p1: usize, p2: usize) -> usize {
-        let c1 = p1 / self.d;
-        let c2 = p2 / self.d;
-        let (min, max) = if c1 > c2 { (c1, c2) } else { (c2, c1) };
-        min * self.n() + max
+    fn points(&self, c: usize) -> Range<usize> {
+        c * self.d..(c + 1) * self.d
     }
 
-    // returns the number of edges between p1s and p2s cells
+    // returns the number of pairs between p1s and p2s cells, which must be different
     fn card(&self, p1: usize, p2: usize) -> usize {
-        self.edges[self.edges_idx(p1, p2)].len()
+        let c2 = self.cell(p2);
+        self.points(self.cell(p1))
+            .filter(|&q| self.cell(self.partner[q]) == c2)
+            .count()
     }
 
-    fn replace_pair(&mut self, idx: usize, p1: usize, p2: usize) {
-        let [old_p1, old_p2] = self.pairs[idx];
-        self.edges[self.edges_idx(old_p1, old_p2)].retain(|x| *x != idx);
-        self.pairs[idx] = [p1, p2];
-        self.edges[self.edges_idx(p1, p2)].push(idx);
+    fn pair(&mut self, p1: usize, p2: usize) {
+        self.partner[p1] = p2;
+        self.partner[p2] = p1;
     }
 
     // returns true if all of the points are in different cells
@@ -48,176 +58,314 @@ impl Pairing {
         ps.iter().map(|&v| v / self.d).sorted().dedup().count() == N
     }
 
-    // iterates all pairs of cells the list of point pairs (as indexs to 'pairs') that connect them
-    fn enumerate(&self) -> impl Iterator<Item = (usize, usize, &SmallVec)> {
-        self.edges
-            .iter()
-            .enumerate()
-            .map(|(c, count)| (c / self.n(), c % self.n(), count))
+    // returns true if p is paired with a different cell and no other pair joins the two cells
+    fn simple(&self, p: usize) -> bool {
+        let q = self.partner[p];
+        self.cell(p) != self.cell(q) && self.card(p, q) == 1
     }
 
-    pub(super) fn cell_pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
-        self.pairs
-            .iter()
-            .map(|&[p1, p2]| (p1 / self.d, p2 / self.d))
+    fn has_loop(&self, c: usize) -> bool {
+        self.points(c).any(|p| self.cell(self.partner[p]) == c)
+    }
+
+    // returns a random point and its partner
+    fn random_pair(&self, rng: &mut impl Rng) -> [usize; 2] {
+        let p = rng.gen_range(0..self.partner.len());
+        [p, self.partner[p]]
     }
+
+    // iterates the cell each point is paired with, so the d neighbours of each cell in turn
+    pub(super) fn neighbour_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.partner.iter().map(|&p| self.cell(p))
+    }
+}
+
+// An l-switching removes the loop p2--p3 by repairing p1--p6, p4--p5 as p1--p2, p3--p4, p5--p6
+fn l_switching_valid(
+    p: &Pairing,
+    [p2, p3]: [usize; 2],
+    [p1, p6]: [usize; 2],
+    [p4, p5]: [usize; 2],
+) -> bool {
+    p.disjoint([p1, p2, p4, p5, p6])
+        && p.card(p1, p6) == 1
+        && p.card(p4, p5) == 1
+        && p.card(p1, p2) == 0
+        && p.card(p3, p4) == 0
+        && p.card(p5, p6) == 0
+}
+
+// A d-switching removes the double pair p2--p6, p3--p7, where p2 and p3 share a cell, by
+// repairing p1--p5, p4--p8 as p1--p2, p3--p4, p5--p6, p7--p8
+fn d_switching_valid(
+    p: &Pairing,
+    [p2, p6]: [usize; 2],
+    [p3, p7]: [usize; 2],
+    [p1, p5]: [usize; 2],
+    [p4, p8]: [usize; 2],
+) -> bool {
+    p.disjoint([p1, p2, p4, p5, p6, p8])
+        && p.card(p1, p5) == 1
+        && p.card(p4, p8) == 1
+        && p.card(p1, p2) == 0
+        && p.card(p3, p4) == 0
+        && p.card(p5, p6) == 0
+        && p.card(p7, p8) == 0
 }
 
-fn rand_pairing(n: usize, d: usize, rng: &mut impl Rng) -> Pairing {
-    assert_eq!((n * d) % 2, 0);
-    let mut permutation: Box<[usize]> = (0..n * d).collect();
-    permutation.shuffle(rng);
-    let map = permutation
-        .chunks_exact(2)
-        .map(|x| match x {
-            &[p1, p2] => [p1, p2],
-            _ => unreachable!(),
-        })
-        .collect();
-    rand_pairing_edges(d, map)
+// Per point and per cell counts shared by the backward switching counts
+struct Simple {
+    // whether each point is in a simple pair, see 'Pairing::simple'
+    points: Box<[bool]>,
+    // the cell each point is paired with
+    partner_cells: Box<[usize]>,
+    // number of simple points in each cell
+    counts: Box<[usize]>,
+    // number of ordered pairs of simple points in each loop free cell, each being the middle
+    // p2, p3 of a 2-path p1--p2, p3--p4 between distinct cells
+    centres: Box<[usize]>,
 }
 
-fn rand_pairing_edges(d: usize, pairs: Box<[[usize; 2]]>) -> Pairing {
-    let n = pairs.len() * 2 / d;
-    let mut edges: Box<[SmallVec]> = repeat_with(SmallVec::default).take(n.pow(2)).collect();
-    let mut res = Pairing {
-        d,
-        pairs,
-        edges: Vec::new().into_boxed_slice(),
-    };
-    res.pairs.iter().enumerate().for_each(|(idx, &[p1, p2])| {
-        edges[res.edges_idx(p1, p2)].push(idx);
-    });
-    res.edges = edges;
+impl Simple {
+    fn new(p: &Pairing) -> Self {
+        let points: Box<[bool]> = (0..p.partner.len()).map(|q| p.simple(q)).collect();
+        let partner_cells = p.neighbour_cells().collect();
+        let counts: Box<[usize]> = (0..p.n())
+            .map(|c| p.points(c).filter(|&q| points[q]).count())
+            .collect();
+        let centres = (0..p.n())
+            .map(|c| match p.has_loop(c) {
+                true => 0,
+                false => counts[c] * counts[c].saturating_sub(1),
+            })
+            .collect();
+        Simple {
+            points,
+            partner_cells,
+            counts,
+            centres,
+        }
+    }
+
+    // returns 'cells' together with the cells paired with cell c, without repeats
+    fn near(&self, p: &Pairing, cells: [usize; 3], c: usize) -> Cells {
+        let mut res = Cells::from_slice(&cells);
+        for &x in &self.partner_cells[p.points(c)] {
+            if !res.contains(&x) {
+                res.push(x)
+            }
+        }
+        res
+    }
+
+    // iterates the 2-paths p1--p2, p3--p4 counted in 'centres' as [p1, p2, p3, p4]
+    fn two_paths<'a>(&'a self, p: &'a Pairing) -> impl Iterator<Item = [usize; 4]> + 'a {
+        (0..p.n())
+            .filter(|&c| self.centres[c] > 0)
+            .flat_map(move |c| p.points(c).cartesian_product(p.points(c)))
+            .filter(|&(p2, p3)| p2 != p3 && self.points[p2] && self.points[p3])
+            .map(|(p2, p3)| [p.partner[p2], p2, p3, p.partner[p3]])
+    }
+}
+
+// Number of ways 'p' can be produced by an l-switching, that is the number of 2-paths
+// p1--p2, p3--p4 and pairs p5--p6 for which the reverse switching gives a valid l-switching
+fn backward_l_switchings(p: &Pairing) -> usize {
+    let simple = Simple::new(p);
+    let total: usize = simple.counts.iter().sum();
+    let mut res = 0;
+    for [p1, p2, _, p4] in simple.two_paths(p) {
+        let (a, c, b) = (p.cell(p1), p.cell(p2), p.cell(p4));
+        // p5--p6 must be simple with p5 outside 'xs' and p6 outside 'ys'
+        let xs = simple.near(p, [a, b, c], b);
+        let ys = simple.near(p, [a, b, c], a);
+        let from_xs: usize = xs.iter().map(|&x| simple.counts[x]).sum();
+        let to_ys: usize = ys.iter().map(|&y| simple.counts[y]).sum();
+        let xs_to_ys = xs
+            .iter()
+            .flat_map(|&x| p.points(x))
+            .filter(|&q| simple.points[q] && ys.contains(&simple.partner_cells[q]))
+            .count();
+        res += total + xs_to_ys - from_xs - to_ys;
+    }
     res
 }
 
-// Returns the a list of possible (p1, p6), (p4, p5) points the could be used for l_forward
-// represents them as indexes into the list of pairs
-fn l_forward_possibilities(l_idx: usize, p: &Pairing) -> Vec<(usize, usize)> {
-    let [p2, p3] = p.pairs[l_idx];
-    assert!(p.same_cell(p2, p3));
-    p.pairs
-        .iter()
-        .enumerate()
-        .flat_map(|(idx1, &[p1, p6])| {
-            p.pairs
-                .iter()
-                .enumerate()
-                .filter(move |(_, &[p4, p5])| {
-                    p.disjoint([p1, p2, p4, p5, p6])
-                        && p.card(p1, p6) == 1
-                        && p.card(p4, p5) == 1
-                        && p.card(p1, p2) == 0
-                        && p.card(p3, p4) == 0
-                        && p.card(p5, p6) == 0
-                })
-                .map(move |(idx2, _)| (idx1, idx2))
-        })
-        .collect()
+// Number of ways 'p' can be produced by a d-switching, that is the number of pairs of 2-paths
+// p1--p2, p3--p4 and p5--p6, p7--p8 for which the reverse switching gives a valid d-switching
+fn backward_d_switchings(p: &Pairing) -> usize {
+    let simple = Simple::new(p);
+    let total: usize = simple.centres.iter().sum();
+    let mut res = 0;
+    for [p1, p2, _, p4] in simple.two_paths(p) {
+        let (a, u, b) = (p.cell(p1), p.cell(p2), p.cell(p4));
+        // the second 2-path p6--p5, p7--p8 must have its middle cell w outside 'ws', p5 outside
+        // 'es' and p8 outside 'fs', count those that do not
+        let ws = simple.near(p, [a, u, b], u);
+        let es = simple.near(p, [a, u, b], a);
+        let fs = simple.near(p, [a, u, b], b);
+        let mut bad: usize = ws.iter().map(|&w| simple.centres[w]).sum();
+        for p5 in es.iter().flat_map(|&e| p.points(e)) {
+            let w = simple.partner_cells[p5];
+            if simple.points[p5] && simple.centres[w] > 0 && !ws.contains(&w) {
+                bad += simple.counts[w] - 1;
+            }
+        }
+        for p8 in fs.iter().flat_map(|&f| p.points(f)) {
+            let (p7, w) = (p.partner[p8], simple.partner_cells[p8]);
+            if simple.points[p8] && simple.centres[w] > 0 && !ws.contains(&w) {
+                bad += p
+                    .points(w)
+                    .filter(|&p6| {
+                        p6 != p7 && simple.points[p6] && !es.contains(&simple.partner_cells[p6])
+                    })
+                    .count();
+            }
+        }
+        res += total - bad;
+    }
+    res
 }
 
-fn no_loops(p: &mut Pairing, loop_idxs: Vec<usize>, rng: &mut impl Rng) -> Option<()> {
-    for loop_idx in loop_idxs {
-        let [p2, p3] = p.pairs[loop_idx];
-        let l_forward_possibilities = l_forward_possibilities(loop_idx, p);
-        let &(idx1, idx2) = l_forward_possibilities.choose(rng).unwrap();
-        let [p1, p6] = p.pairs[idx1];
-        let [p4, p5] = p.pairs[idx2];
-        // TODO randomly fail sometimes
-        p.replace_pair(loop_idx, p1, p2);
-        p.replace_pair(idx1, p3, p4);
-        p.replace_pair(idx2, p5, p6);
+// Restarts with probability 1 - lower / b where b is the number of backward switchings into the
+// current pairing, which is between 'lower' and 'upper' for every pairing in its class
+// Counting b takes a pass over the whole pairing, so it is only done when the outcome depends on it,
+// which for large n is rarely as the bounds are then close
+fn b_reject(lower: i64, upper: i64, b: impl FnOnce() -> usize, rng: &mut impl Rng) -> Option<()> {
+    if lower <= 0 {
+        return None;
     }
-    Some(())
+    let (lower, upper) = (lower as u64, upper as u64);
+    // x is uniform below 'upper', and so below b when it is below 'lower'
+    let x = rng.gen_range(0..upper);
+    if x < lower {
+        return Some(());
+    }
+    let b = b() as u64;
+    debug_assert!(
+        (lower..=upper).contains(&b),
+        "backward switchings {b} outside bounds {lower}..={upper}"
+    );
+    // accept with probability lower / b overall by redrawing below b when x was not below b
+    (x >= b && rng.gen_range(0..b) < lower).then_some(())
 }
 
-// Returns the a list of possible (p1, p5), (p4, p8) points the could be used for d_forward
-// represents them as indexes into the list of pairs
-fn d_forward_possibilities(d_idx1: usize, d_idx2: usize, p: &Pairing) -> Vec<(usize, usize)> {
-    let [p2, p6] = p.pairs[d_idx1];
-    let [p3, p7] = p.pairs[d_idx2];
-    assert!(p.same_cell(p2, p3));
-    assert!(p.same_cell(p6, p7));
-
-    p.pairs
-        .iter()
-        .enumerate()
-        .flat_map(|(idx1, &[p1, p5])| {
-            p.pairs
-                .iter()
-                .enumerate()
-                .filter(move |(_, &[p4, p8])| {
-                    p.disjoint([p1, p2, p4, p5, p6, p8])
-                        && p.card(p1, p5) == 1
-                        && p.card(p4, p8) == 1
-                        && p.card(p1, p2) == 0
-                        && p.card(p3, p4) == 0
-                        && p.card(p5, p6) == 0
-                        && p.card(p7, p8) == 0
-                })
-                .map(move |(idx2, _)| (idx1, idx2))
-        })
-        .collect()
+// Removes the loops one at a time with l-switchings
+// Choosing a random loop, orientation and pair of points gives each of the l-switchings
+// applicable to a pairing with 'l' loops probability 1 / (2 * l * M1^2), restarting whenever the
+// candidate is not a valid switching (the f-rejection of the paper). The b-rejection then
+// makes every pairing with one fewer loop equally likely
+fn no_loops(
+    p: &mut Pairing,
+    mut loops: Vec<[usize; 2]>,
+    doubles: usize,
+    rng: &mut impl Rng,
+) -> Option<()> {
+    let (m1, m2) = (p.partner.len() as i64, (p.partner.len() * (p.d - 1)) as i64);
+    let (d, doubles) = (p.d as i64, doubles as i64);
+    while !loops.is_empty() {
+        let mut l = loops.swap_remove(rng.gen_range(0..loops.len()));
+        l.shuffle(rng);
+        let [p1, p6] = p.random_pair(rng);
+        let [p4, p5] = p.random_pair(rng);
+        if !l_switching_valid(p, l, [p1, p6], [p4, p5]) {
+            return None;
+        }
+        let [p2, p3] = l;
+        p.pair(p1, p2);
+        p.pair(p3, p4);
+        p.pair(p5, p6);
+        // bounds on the 2-paths p1--p2, p3--p4 and the pairs p5--p6 of the backward switchings
+        let loops = loops.len() as i64;
+        let paths = m2 - loops * d * (d - 1);
+        let pairs = m1 - 2 * loops - 4 * doubles;
+        let lower = match (paths - 8 * doubles * (d - 1), pairs - 2 * d * d - 4 * d) {
+            (paths, pairs) if paths > 0 && pairs > 0 => paths * pairs,
+            _ => 0,
+        };
+        b_reject(lower, paths * pairs, || backward_l_switchings(p), rng)?;
+    }
+    Some(())
 }
 
-fn no_doubles(p: &mut Pairing, double_idxs: Vec<(usize, usize)>, rng: &mut impl Rng) -> Option<()> {
-    for (d_idx1, d_idx2) in double_idxs {
-        let [p2, p6] = p.pairs[d_idx1];
-        let [p3, p7] = &mut p.pairs[d_idx2];
-        if p2 / p.d == *p7 / p.d {
-            swap(p3, p7) // reorder to force p2--p3, and p6--p7
+// Removes the double pairs one at a time with d-switchings, restarting as in 'no_loops', each
+// d-switching applicable to a pairing with D double pairs having probability 1 / (4 * D * M1^2)
+fn no_doubles(
+    p: &mut Pairing,
+    mut doubles: Vec<[[usize; 2]; 2]>,
+    rng: &mut impl Rng,
+) -> Option<()> {
+    let m2 = (p.partner.len() * (p.d - 1)) as i64;
+    let d = p.d as i64;
+    while !doubles.is_empty() {
+        let mut double = doubles.swap_remove(rng.gen_range(0..doubles.len()));
+        double.shuffle(rng);
+        if rng.gen() {
+            double.iter_mut().for_each(|pair| pair.reverse());
+        }
+        let [[p2, p6], [p3, p7]] = double;
+        let [p1, p5] = p.random_pair(rng);
+        let [p4, p8] = p.random_pair(rng);
+        if !d_switching_valid(p, [p2, p6], [p3, p7], [p1, p5], [p4, p8]) {
+            return None;
         }
-        let [p3, p7] = [*p3, *p7];
-        let d_forward_possibilities = d_forward_possibilities(d_idx1, d_idx2, p);
-        let &(idx1, idx2) = d_forward_possibilities.choose(rng).unwrap();
-        let [p1, p5] = p.pairs[idx1];
-        let [p4, p8] = p.pairs[idx2];
-        // TODO randomly fail sometimes
-        p.replace_pair(d_idx1, p1, p2);
-        p.replace_pair(d_idx2, p3, p4);
-        p.replace_pair(idx1, p5, p6);
-        p.replace_pair(idx2, p7, p8);
+        p.pair(p1, p2);
+        p.pair(p3, p4);
+        p.pair(p5, p6);
+        p.pair(p7, p8);
+        // bounds on the first 2-path p1--p2, p3--p4 and on the second p5--p6, p7--p8 given the
+        // first, of the backward switchings
+        let paths = m2 - 8 * (d - 1) * doubles.len() as i64;
+        let second = paths - d * (d - 1) * (3 * d + 5);
+        let lower = if paths > 0 && second > 0 {
+            paths * second
+        } else {
+            0
+        };
+        b_reject(lower, m2 * m2, || backward_d_switchings(p), rng)?;
     }
     Some(())
 }
 
 // Core of the deg algorithm, return None to indicate we need to retry
 fn try_deg(n: usize, d: usize, rng: &mut impl Rng) -> Option<Pairing> {
-    let mut pairing = rand_pairing(n, d, rng);
-    let m = n * d;
-    let m2 = n * d * (d - 1);
-    let mut double_pairs = vec![];
+    let mut pairing = Pairing::random(n, d, rng);
+    let mut doubles = vec![];
     let mut loops = vec![];
-    for (c1, c2, edge_group) in pairing.enumerate() {
-        let card = edge_group.len();
+    for p in 0..n * d {
+        let q = pairing.partner[p];
+        let (c1, c2) = (pairing.cell(p), pairing.cell(q));
+        // look for other pairs between the same cells
+        let mut others = pairing
+            .points(c1)
+            .filter(|&x| x != p && x != q && pairing.cell(pairing.partner[x]) == c2);
         if c1 == c2 {
-            //loop
-            match card {
-                0 => (),
-                1 => loops.push(edge_group[0]),
-                _ => return None,
+            if others.next().is_some() {
+                return None;
+            } else if p < q {
+                loops.push([p, q]);
             }
-        } else {
-            match card {
-                0 | 1 => (),
-                2 => double_pairs.push((edge_group[0], edge_group[1])),
-                _ => return None,
+        } else if c1 < c2 {
+            match (others.next(), others.next()) {
+                (None, _) => (),
+                (Some(x), None) if p < x => doubles.push([[p, q], [x, pairing.partner[x]]]),
+                (Some(_), None) => (),
+                (Some(_), Some(_)) => return None,
             }
         }
     }
-    if double_pairs.len() > (m2 / m).pow(2) {
-        return None;
-    }
-    if loops.len() > m2 / m {
+    let max_loops = d.saturating_sub(1);
+    if doubles.len() > max_loops.pow(2) || loops.len() > max_loops {
         return None;
     }
-    no_loops(&mut pairing, loops, rng)?;
-    no_doubles(&mut pairing, double_pairs, rng)?;
+    let double_count = doubles.len();
+    no_loops(&mut pairing, loops, double_count, rng)?;
+    no_doubles(&mut pairing, doubles, rng)?;
     Some(pairing)
 }
 
 // Algorithm based on https://doi.org/10.1016/0196-6774(90)90029-E
+// Returns a pairing whose cell pairs form a uniformly random simple d-regular graph
 pub(super) fn deg(n: usize, d: usize, rng: &mut impl Rng) -> Pairing {
     loop {
         match try_deg(n, d, rng) {
@@ -226,3 +374,79 @@ pub(super) fn deg(n: usize, d: usize, rng: &mut impl Rng) -> Pairing {
         }
     }
 }
+
+// Draws pairings until one is simple, so its cell pairs form a uniformly random simple d-regular
+// graph
+pub(super) fn configuration(n: usize, d: usize, rng: &mut impl Rng) -> Pairing {
+    loop {
+        let pairing = Pairing::random(n, d, rng);
+        if (0..n * d).all(|p| pairing.simple(p)) {
+            return pairing;
+        }
+    }
+}
+
+#[test]
+fn test_backward_switchings() {
+    // compare against reversing every possible switching by brute force
+    let mut rng = crate::rng::seeded(0);
+    for (n, d) in [(8, 3), (10, 3), (6, 4)] {
+        for _ in 0..5 {
+            let p = Pairing::random(n, d, &mut rng);
+            let distinct = |ps: &[usize]| ps.iter().sorted().dedup().count() == ps.len();
+            let loops_in = |q: &Pairing, c: usize| {
+                q.points(c).filter(|&x| q.cell(q.partner[x]) == c).count() / 2
+            };
+            let mut l_switchings = 0;
+            let mut d_switchings = 0;
+            for p2 in 0..n * d {
+                for p3 in p.points(p.cell(p2)).filter(|&p3| p3 != p2) {
+                    let (p1, p4) = (p.partner[p2], p.partner[p3]);
+                    for p5 in 0..n * d {
+                        let p6 = p.partner[p5];
+                        if !distinct(&[p1, p2, p3, p4, p5, p6]) {
+                            continue;
+                        }
+                        let mut q = Pairing {
+                            d,
+                            partner: p.partner.clone(),
+                        };
+                        q.pair(p2, p3);
+                        q.pair(p1, p6);
+                        q.pair(p4, p5);
+                        if loops_in(&q, q.cell(p2)) == 1
+                            && l_switching_valid(&q, [p2, p3], [p1, p6], [p4, p5])
+                        {
+                            l_switchings += 1;
+                        }
+                    }
+                    for p6 in 0..n * d {
+                        for p7 in p.points(p.cell(p6)).filter(|&p7| p7 != p6) {
+                            let (p5, p8) = (p.partner[p6], p.partner[p7]);
+                            if !distinct(&[p1, p2, p3, p4, p5, p6, p7, p8]) {
+                                continue;
+                            }
+                            let mut q = Pairing {
+                                d,
+                                partner: p.partner.clone(),
+                            };
+                            q.pair(p2, p6);
+                            q.pair(p3, p7);
+                            q.pair(p1, p5);
+                            q.pair(p4, p8);
+                            if q.card(p2, p6) == 2
+                                && loops_in(&q, q.cell(p2)) == 0
+                                && loops_in(&q, q.cell(p6)) == 0
+                                && d_switching_valid(&q, [p2, p6], [p3, p7], [p1, p5], [p4, p8])
+                            {
+                                d_switchings += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            assert_eq!(backward_l_switchings(&p), l_switchings);
+            assert_eq!(backward_d_switchings(&p), d_switchings);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::{Display, Formatter};
+
+use rand::Rng;
+
+use super::{Bounded, Graph};
+use crate::Error;
+
+// The disjoint union of two graphs, side by side with no edges between them
+// The vertices of G come first, so vertex v of H has index 'g.n() + h.as_idx(v)'
+pub struct DisjointUnionGraph<G: Graph, H: Graph> {
+    g: G,
+    h: H,
+    g_m: usize,
+    h_m: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub enum UnionVertex<V, U> {
+    Left(V),
+    Right(U),
+}
+
+// Written as the vertex of its part with the part, L or R, in front
+impl<V: Display, U: Display> Display for UnionVertex<V, U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnionVertex::Left(v) => write!(f, "L{v}"),
+            UnionVertex::Right(u) => write!(f, "R{u}"),
+        }
+    }
+}
+
+impl<G: Graph, H: Graph> Graph for DisjointUnionGraph<G, H> {
+    type Vertex = UnionVertex<G::Vertex, H::Vertex>;
+    type VIter = std::iter::Chain<
+        std::iter::Map<G::VIter, fn(G::Vertex) -> Self::Vertex>,
+        std::iter::Map<H::VIter, fn(H::Vertex) -> Self::Vertex>,
+    >;
+    type NIter = std::vec::IntoIter<Self::Vertex>;
+
+    fn n(&self) -> usize {
+        self.g.n() + self.h.n()
+    }
+
+    fn d(&self) -> Option<usize> {
+        self.g.d().filter(|&d| self.h.d() == Some(d))
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        match v {
+            UnionVertex::Left(v) => self.g.degree(v),
+            UnionVertex::Right(u) => self.h.degree(u),
+        }
+    }
+
+    fn m(&self) -> usize {
+        self.g_m + self.h_m
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        match v {
+            UnionVertex::Left(v) => self.g.as_idx(v),
+            UnionVertex::Right(u) => self.g.n() + self.h.as_idx(u),
+        }
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        let left: fn(G::Vertex) -> Self::Vertex = UnionVertex::Left;
+        let right: fn(H::Vertex) -> Self::Vertex = UnionVertex::Right;
+        self.g
+            .iter_vertices()
+            .map(left)
+            .chain(self.h.iter_vertices().map(right))
+    }
+
+    fn iter_neighbours(&self, v: Self::Vertex) -> Self::NIter {
+        let res: Vec<_> = match v {
+            UnionVertex::Left(v) => self.g.iter_neighbours(v).map(UnionVertex::Left).collect(),
+            UnionVertex::Right(u) => self.h.iter_neighbours(u).map(UnionVertex::Right).collect(),
+        };
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        match (v, u) {
+            (UnionVertex::Left(v), UnionVertex::Left(u)) => self.g.has_edge(v, u),
+            (UnionVertex::Right(v), UnionVertex::Right(u)) => self.h.has_edge(v, u),
+            _ => false,
+        }
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // pick a part with probability proportional to its number of edges
+        if rng.gen_range(0..self.m()) < self.g_m {
+            let (v, u) = self.g.random_edge(rng);
+            (UnionVertex::Left(v), UnionVertex::Left(u))
+        } else {
+            let (v, u) = self.h.random_edge(rng);
+            (UnionVertex::Right(v), UnionVertex::Right(u))
+        }
+    }
+}
+
+impl<G: Bounded, H: Bounded> Bounded for DisjointUnionGraph<G, H> {
+    fn is_boundary(&self, v: Self::Vertex) -> bool {
+        match v {
+            UnionVertex::Left(v) => self.g.is_boundary(v),
+            UnionVertex::Right(u) => self.h.is_boundary(u),
+        }
+    }
+}
+
+impl<G: Graph, H: Graph> DisjointUnionGraph<G, H> {
+    pub fn new(g: G, h: H) -> Result<Self, Error> {
+        let (g_m, h_m) = (g.m(), h.m());
+        if g.n().checked_add(h.n()).is_none() || g_m.checked_add(h_m).is_none() {
+            return Err(Error::TooManyVertices);
+        }
+        Ok(DisjointUnionGraph { g, h, g_m, h_m })
+    }
+
+    pub fn parts(&self) -> (&G, &H) {
+        (&self.g, &self.h)
+    }
+}
+
+#[cfg(test)]
+use super::{
+    full_graph::Vertex, ring_graph::RingVertex, FullGraph, GridGraph, RingGraph, StarGraph,
+};
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    let graph = DisjointUnionGraph::new(RingGraph::new(5).unwrap(), StarGraph::new(4).unwrap());
+    graph.unwrap().validate(&mut rng).assert_valid();
+    let graph = DisjointUnionGraph::new(FullGraph::new(1).unwrap(), RingGraph::new(3).unwrap());
+    graph.unwrap().validate(&mut rng).assert_valid();
+    let inner = DisjointUnionGraph::new(FullGraph::new(4).unwrap(), RingGraph::new(3).unwrap());
+    let graph = DisjointUnionGraph::new(inner.unwrap(), FullGraph::new(3).unwrap());
+    graph.unwrap().validate(&mut rng).assert_valid();
+}
+
+#[test]
+fn test_structure() {
+    let graph =
+        DisjointUnionGraph::new(RingGraph::new(4).unwrap(), FullGraph::new(3).unwrap()).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (7, Some(2), 7));
+    let labels: Vec<String> = graph.iter_vertices().map(|v| v.to_string()).collect();
+    assert_eq!(labels, ["L0", "L1", "L2", "L3", "R0", "R1", "R2"]);
+    let (v, u) = (
+        UnionVertex::Left(RingVertex(3)),
+        UnionVertex::Right(Vertex(0)),
+    );
+    assert_eq!((graph.as_idx(v), graph.as_idx(u)), (3, 4));
+    assert!(!graph.has_edge(v, u));
+    let graph =
+        DisjointUnionGraph::new(RingGraph::new(4).unwrap(), FullGraph::new(4).unwrap()).unwrap();
+    assert_eq!(graph.d(), None);
+    // each part gets its share of the random edges
+    let mut rng = crate::rng::seeded(1);
+    let left = (0..4000)
+        .filter(|_| matches!(graph.random_edge(&mut rng).0, UnionVertex::Left(_)))
+        .count();
+    assert!((1450..1750).contains(&left), "{left}");
+    // each part keeps its boundary
+    let graph =
+        DisjointUnionGraph::new(GridGraph::new(&[3]).unwrap(), GridGraph::new(&[2]).unwrap());
+    let graph = graph.unwrap();
+    let boundary: Vec<bool> = graph
+        .iter_vertices()
+        .map(|v| graph.is_boundary(v))
+        .collect();
+    assert_eq!(boundary, [true, false, true, true, true]);
+}
@@ -0,0 +1,274 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+
+use super::Graph;
+use crate::Error;
+
+// The k-ary fat-tree of Al-Fares et al. for even k, including its servers
+// There are k pods, each with k / 2 edge switches and k / 2 aggregation switches, every edge
+// switch being joined to k / 2 servers and to every aggregation switch in its pod, and
+// (k / 2)^2 core switches, core switch a * k / 2 + j being joined to aggregation switch a of every
+// pod, so every switch has k neighbours and every server one
+// Vertices are indexed servers first, then edge, aggregation and core switches, those in each pod
+// being numbered consecutively
+pub struct FatTreeGraph {
+    k: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct FatTreeVertex(usize);
+
+impl Display for FatTreeVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+// The kind of a vertex, with its pod and position in the pod where it has one
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FatTreeNode {
+    // servers are numbered within their edge switch
+    Server { pod: usize, edge: usize, idx: usize },
+    Edge { pod: usize, idx: usize },
+    Aggregation { pod: usize, idx: usize },
+    Core { idx: usize },
+}
+
+impl Graph for FatTreeGraph {
+    type Vertex = FatTreeVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> FatTreeVertex>;
+    type NIter = std::vec::IntoIter<FatTreeVertex>;
+
+    fn n(&self) -> usize {
+        // servers, edge and aggregation switches, and core switches
+        self.servers() + self.k * self.k + self.half() * self.half()
+    }
+
+    fn d(&self) -> Option<usize> {
+        None
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        match self.node(v) {
+            FatTreeNode::Server { .. } => 1,
+            _ => self.k,
+        }
+    }
+
+    fn m(&self) -> usize {
+        // k^3 / 4 edges between each pair of consecutive layers
+        3 * self.servers()
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(FatTreeVertex)
+    }
+
+    fn iter_neighbours(&self, v: FatTreeVertex) -> Self::NIter {
+        let h = self.half();
+        let vertex = |node| self.vertex(node);
+        let res: Vec<_> = match self.node(v) {
+            FatTreeNode::Server { pod, edge, .. } => {
+                vec![vertex(FatTreeNode::Edge { pod, idx: edge })]
+            }
+            FatTreeNode::Edge { pod, idx } => (0..h)
+                .map(|i| FatTreeNode::Server {
+                    pod,
+                    edge: idx,
+                    idx: i,
+                })
+                .chain((0..h).map(|i| FatTreeNode::Aggregation { pod, idx: i }))
+                .map(vertex)
+                .collect(),
+            FatTreeNode::Aggregation { pod, idx } => (0..h)
+                .map(|i| FatTreeNode::Edge { pod, idx: i })
+                .chain((0..h).map(|j| FatTreeNode::Core { idx: idx * h + j }))
+                .map(vertex)
+                .collect(),
+            FatTreeNode::Core { idx } => (0..self.k)
+                .map(|pod| FatTreeNode::Aggregation { pod, idx: idx / h })
+                .map(vertex)
+                .collect(),
+        };
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        let h = self.half();
+        let joined = |v, u| match (v, u) {
+            (FatTreeNode::Server { pod, edge, .. }, FatTreeNode::Edge { pod: p, idx }) => {
+                (pod, edge) == (p, idx)
+            }
+            (FatTreeNode::Edge { pod, .. }, FatTreeNode::Aggregation { pod: p, .. }) => pod == p,
+            (FatTreeNode::Aggregation { idx, .. }, FatTreeNode::Core { idx: c }) => idx == c / h,
+            _ => false,
+        };
+        let (v, u) = (self.node(v), self.node(u));
+        joined(v, u) || joined(u, v)
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // each layer has the same number of edges, and each of its lower ends the same number of
+        // edges up
+        let h = self.half();
+        let pod = rng.gen_range(0..self.k);
+        let (lower, upper) = match rng.gen_range(0..3) {
+            0 => {
+                let edge = rng.gen_range(0..h);
+                (
+                    FatTreeNode::Server {
+                        pod,
+                        edge,
+                        idx: rng.gen_range(0..h),
+                    },
+                    FatTreeNode::Edge { pod, idx: edge },
+                )
+            }
+            1 => (
+                FatTreeNode::Edge {
+                    pod,
+                    idx: rng.gen_range(0..h),
+                },
+                FatTreeNode::Aggregation {
+                    pod,
+                    idx: rng.gen_range(0..h),
+                },
+            ),
+            _ => {
+                let idx = rng.gen_range(0..h);
+                (
+                    FatTreeNode::Aggregation { pod, idx },
+                    FatTreeNode::Core {
+                        idx: idx * h + rng.gen_range(0..h),
+                    },
+                )
+            }
+        };
+        let (v, u) = (self.vertex(lower), self.vertex(upper));
+        if rng.gen() {
+            (v, u)
+        } else {
+            (u, v)
+        }
+    }
+}
+
+impl FatTreeGraph {
+    pub fn new(k: usize) -> Result<Self, Error> {
+        if k == 0 {
+            return Err(Error::NoVertices);
+        }
+        if k % 2 == 1 {
+            return Err(Error::OddArity { k });
+        }
+        // the servers are the most numerous, so n is below 2 k^3
+        let n = k
+            .checked_mul(k)
+            .and_then(|k2| k2.checked_mul(k))
+            .and_then(|k3| k3.checked_mul(2));
+        match n {
+            None => Err(Error::TooManyVertices),
+            Some(_) => Ok(FatTreeGraph { k }),
+        }
+    }
+
+    pub fn node(&self, v: FatTreeVertex) -> FatTreeNode {
+        let h = self.half();
+        let pod_size = h * h;
+        let mut idx = v.0;
+        if idx < self.servers() {
+            let (pod, idx) = (idx / pod_size, idx % pod_size);
+            return FatTreeNode::Server {
+                pod,
+                edge: idx / h,
+                idx: idx % h,
+            };
+        }
+        idx -= self.servers();
+        if idx < self.k * h {
+            return FatTreeNode::Edge {
+                pod: idx / h,
+                idx: idx % h,
+            };
+        }
+        idx -= self.k * h;
+        if idx < self.k * h {
+            return FatTreeNode::Aggregation {
+                pod: idx / h,
+                idx: idx % h,
+            };
+        }
+        FatTreeNode::Core {
+            idx: idx - self.k * h,
+        }
+    }
+
+    pub fn vertex(&self, node: FatTreeNode) -> FatTreeVertex {
+        let h = self.half();
+        FatTreeVertex(match node {
+            FatTreeNode::Server { pod, edge, idx } => (pod * h + edge) * h + idx,
+            FatTreeNode::Edge { pod, idx } => self.servers() + pod * h + idx,
+            FatTreeNode::Aggregation { pod, idx } => self.servers() + (self.k + pod) * h + idx,
+            FatTreeNode::Core { idx } => self.servers() + 2 * self.k * h + idx,
+        })
+    }
+
+    fn half(&self) -> usize {
+        self.k / 2
+    }
+
+    fn servers(&self) -> usize {
+        self.k * self.half() * self.half()
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for k in [2, 4, 6, 8] {
+        FatTreeGraph::new(k)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(FatTreeGraph::new(0).err(), Some(Error::NoVertices));
+    assert_eq!(FatTreeGraph::new(5).err(), Some(Error::OddArity { k: 5 }));
+    assert_eq!(
+        FatTreeGraph::new(1 << 32).err(),
+        Some(Error::TooManyVertices)
+    );
+}
+
+#[test]
+fn test_structure() {
+    // the usual 4-ary fat-tree has 16 servers and 20 switches
+    let graph = FatTreeGraph::new(4).unwrap();
+    assert_eq!((graph.n(), graph.m()), (36, 48));
+    for v in graph.iter_vertices() {
+        assert_eq!(graph.vertex(graph.node(v)).0, v.0);
+    }
+    let server = graph.vertex(FatTreeNode::Server {
+        pod: 3,
+        edge: 1,
+        idx: 0,
+    });
+    let neighbours: Vec<_> = graph
+        .iter_neighbours(server)
+        .map(|v| graph.node(v))
+        .collect();
+    assert_eq!(neighbours, [FatTreeNode::Edge { pod: 3, idx: 1 }]);
+    let core = graph.vertex(FatTreeNode::Core { idx: 3 });
+    let neighbours: Vec<_> = graph.iter_neighbours(core).map(|v| graph.node(v)).collect();
+    assert_eq!(
+        neighbours,
+        (0..4)
+            .map(|pod| FatTreeNode::Aggregation { pod, idx: 1 })
+            .collect::<Vec<_>>()
+    );
+}
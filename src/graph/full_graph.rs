@@ -1,13 +1,14 @@
 use std::fmt::{Display, Formatter};
-use std::iter::{Enumerate, FilterMap, Repeat, Take};
+use std::iter::{Enumerate, FilterMap, RepeatN};
 use std::ops::Range;
 
 use rand::Rng;
 
 use crate::graph::Graph;
+use crate::Error;
 
 pub struct FullGraph {
-    pub n: usize,
+    n: usize,
 }
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
@@ -22,14 +23,14 @@ impl Display for Vertex {
 impl Graph for FullGraph {
     type Vertex = Vertex;
     type VIter = std::iter::Map<Range<usize>, fn(usize) -> Vertex>;
-    type NIter = FilterMap<Enumerate<Take<Repeat<Vertex>>>, fn((usize, Vertex)) -> Option<Vertex>>;
+    type NIter = FilterMap<Enumerate<RepeatN<Vertex>>, fn((usize, Vertex)) -> Option<Vertex>>;
 
     fn n(&self) -> usize {
         self.n
     }
 
-    fn d(&self) -> usize {
-        self.n() - 1
+    fn d(&self) -> Option<usize> {
+        Some(self.n() - 1)
     }
 
     fn as_idx(&self, v: Self::Vertex) -> usize {
@@ -37,12 +38,11 @@ impl Graph for FullGraph {
     }
 
     fn iter_vertices(&self) -> Self::VIter {
-        (0..self.n()).map(|x| Vertex(x))
+        (0..self.n()).map(Vertex)
     }
 
     fn iter_neighbours(&self, v: Vertex) -> Self::NIter {
-        std::iter::repeat(v)
-            .take(self.n())
+        std::iter::repeat_n(v, self.n())
             .enumerate()
             .filter_map(|(u, v)| if v.0 == u { None } else { Some(Vertex(u)) })
     }
@@ -62,9 +62,20 @@ impl Graph for FullGraph {
     }
 }
 
+impl FullGraph {
+    pub fn new(n: usize) -> Result<Self, Error> {
+        match n {
+            0 => Err(Error::NoVertices),
+            _ => Ok(FullGraph { n }),
+        }
+    }
+}
+
 #[test]
 fn test_valid() {
-    FullGraph { n: 2 }.validate();
-    FullGraph { n: 3 }.validate();
-    FullGraph { n: 10 }.validate();
+    let mut rng = crate::rng::seeded(0);
+    FullGraph { n: 2 }.validate(&mut rng).assert_valid();
+    FullGraph { n: 3 }.validate(&mut rng).assert_valid();
+    FullGraph { n: 10 }.validate(&mut rng).assert_valid();
+    assert_eq!(FullGraph::new(0).err(), Some(Error::NoVertices));
 }
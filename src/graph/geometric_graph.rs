@@ -0,0 +1,287 @@
+use rand::Rng;
+
+use super::{CsrGraph, CsrVertex, Embedded, Graph};
+use crate::Error;
+
+// A random geometric graph, n points placed uniformly at random in the unit cube [0, 1)^k with
+// points at distance at most 'radius' joined
+// If periodic the cube wraps around into a torus, so there are no boundary effects
+// Vertex v is the v'th point placed, the edges being stored as a 'CsrGraph'
+pub struct GeometricGraph {
+    graph: CsrGraph,
+    k: usize,
+    // the coordinates of vertex v are 'points[v * k..(v + 1) * k]'
+    points: Box<[f64]>,
+    radius: f64,
+    periodic: bool,
+}
+
+impl Graph for GeometricGraph {
+    type Vertex = CsrVertex;
+    type VIter = <CsrGraph as Graph>::VIter;
+    type NIter = <CsrGraph as Graph>::NIter;
+
+    fn n(&self) -> usize {
+        self.graph.n()
+    }
+
+    fn d(&self) -> Option<usize> {
+        self.graph.d()
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        self.graph.degree(v)
+    }
+
+    fn m(&self) -> usize {
+        self.graph.m()
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        self.graph.as_idx(v)
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        self.graph.iter_vertices()
+    }
+
+    fn iter_neighbours(&self, v: CsrVertex) -> Self::NIter {
+        self.graph.iter_neighbours(v)
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        self.graph.has_edge(v, u)
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        self.graph.random_edge(rng)
+    }
+}
+
+impl Embedded for GeometricGraph {
+    fn dimensions(&self) -> usize {
+        self.k
+    }
+
+    fn position(&self, v: Self::Vertex) -> &[f64] {
+        self.point(v)
+    }
+}
+
+impl GeometricGraph {
+    // Fails if 'random' would, without placing any points
+    pub fn check_random(n: usize, k: usize, radius: f64) -> Result<(), Error> {
+        if n == 0 {
+            Err(Error::NoVertices)
+        } else if n.checked_mul(k).is_none() {
+            Err(Error::TooManyVertices)
+        } else if radius.is_nan() || radius < 0.0 {
+            Err(Error::InvalidRadius)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn random(
+        n: usize,
+        k: usize,
+        radius: f64,
+        periodic: bool,
+        rng: &mut impl Rng,
+    ) -> Result<Self, Error> {
+        Self::check_random(n, k, radius)?;
+        let points = (0..n * k).map(|_| rng.gen()).collect();
+        Self::from_points(n, k, points, radius, periodic)
+    }
+
+    // Joins the points within 'radius' by splitting the cube into cells with sides of at least
+    // 'radius', so only points in the same or adjacent cells need comparing
+    // There are at most n cells, so for small radii there are O(1) points per cell on average
+    fn from_points(
+        n: usize,
+        k: usize,
+        points: Box<[f64]>,
+        radius: f64,
+        periodic: bool,
+    ) -> Result<Self, Error> {
+        let mut cells_per_side =
+            ((1.0 / radius).min((n as f64).powf(1.0 / k as f64)) as usize).max(1);
+        while cells_per_side > 1
+            && cells_per_side
+                .checked_pow(k as u32)
+                .is_none_or(|cells| cells > n)
+        {
+            cells_per_side -= 1;
+        }
+        let cells = cells_per_side.pow(k as u32);
+        let cell_coordinate =
+            |x: f64| ((x * cells_per_side as f64) as usize).min(cells_per_side - 1);
+        let cell_of = |v: usize| {
+            points[v * k..(v + 1) * k]
+                .iter()
+                .fold(0, |cell, &x| cell * cells_per_side + cell_coordinate(x))
+        };
+        // the vertices sorted by cell, those in cell c being 'order[starts[c]..starts[c + 1]]'
+        let mut starts = vec![0; cells + 1];
+        for v in 0..n {
+            starts[cell_of(v) + 1] += 1;
+        }
+        for c in 0..cells {
+            starts[c + 1] += starts[c];
+        }
+        let mut fill = starts.clone();
+        let mut order = vec![0; n];
+        for v in 0..n {
+            let cell = cell_of(v);
+            order[fill[cell]] = v;
+            fill[cell] += 1;
+        }
+        let mut edges = vec![];
+        for cell in 0..cells {
+            if starts[cell] == starts[cell + 1] {
+                continue;
+            }
+            // the cells adjacent to this one, built up a dimension at a time, without repeats
+            // when the cube wraps around with fewer than three cells per side
+            let mut adjacent = vec![0];
+            for dim in (0..k).rev() {
+                let c = cell / cells_per_side.pow(dim as u32) % cells_per_side;
+                let mut coordinates = vec![c];
+                if c > 0 || periodic {
+                    coordinates.push((c + cells_per_side - 1) % cells_per_side);
+                }
+                if c + 1 < cells_per_side || periodic {
+                    coordinates.push((c + 1) % cells_per_side);
+                }
+                coordinates.sort_unstable();
+                coordinates.dedup();
+                adjacent = adjacent
+                    .iter()
+                    .flat_map(|&o| coordinates.iter().map(move |&c| o * cells_per_side + c))
+                    .collect();
+            }
+            for other in adjacent {
+                for &v in &order[starts[cell]..starts[cell + 1]] {
+                    for &u in &order[starts[other]..starts[other + 1]] {
+                        if v < u && distance_squared(&points, k, v, u, periodic) <= radius * radius
+                        {
+                            edges.push((v, u));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(GeometricGraph {
+            graph: CsrGraph::from_edges(n, &edges)?,
+            k,
+            points,
+            radius,
+            periodic,
+        })
+    }
+
+    // The coordinates of the point 'v' was placed at
+    pub fn point(&self, v: CsrVertex) -> &[f64] {
+        let v = self.as_idx(v);
+        &self.points[v * self.k..(v + 1) * self.k]
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn is_periodic(&self) -> bool {
+        self.periodic
+    }
+}
+
+fn distance_squared(points: &[f64], k: usize, v: usize, u: usize, periodic: bool) -> f64 {
+    (0..k)
+        .map(|i| {
+            let diff = (points[v * k + i] - points[u * k + i]).abs();
+            let diff = if periodic { diff.min(1.0 - diff) } else { diff };
+            diff * diff
+        })
+        .sum()
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for (n, k, radius) in [
+        (1, 2, 0.1),
+        (100, 2, 0.1),
+        (100, 2, 0.0),
+        (100, 1, 0.02),
+        (200, 3, 0.25),
+        (50, 2, 0.8),
+        (50, 2, 2.0),
+        (30, 0, 0.5),
+    ] {
+        for periodic in [false, true] {
+            GeometricGraph::random(n, k, radius, periodic, &mut rng)
+                .unwrap()
+                .validate(&mut rng)
+                .assert_valid();
+        }
+    }
+    assert_eq!(
+        GeometricGraph::random(0, 2, 0.1, false, &mut rng).err(),
+        Some(Error::NoVertices)
+    );
+    assert_eq!(
+        GeometricGraph::random(10, 2, -0.1, false, &mut rng).err(),
+        Some(Error::InvalidRadius)
+    );
+    assert_eq!(
+        GeometricGraph::check_random(10, 2, f64::NAN),
+        Err(Error::InvalidRadius)
+    );
+}
+
+#[test]
+fn test_structure() {
+    // the cell lists find exactly the pairs within the radius
+    let mut rng = crate::rng::seeded(1);
+    for (n, k, radius) in [
+        (300, 2, 0.07),
+        (300, 1, 0.004),
+        (200, 3, 0.2),
+        (100, 2, 0.45),
+    ] {
+        for periodic in [false, true] {
+            let graph = GeometricGraph::random(n, k, radius, periodic, &mut rng).unwrap();
+            let mut m = 0;
+            for v in graph.iter_vertices() {
+                assert_eq!(graph.point(v).len(), k);
+                for u in graph.iter_vertices() {
+                    let (v_idx, u_idx) = (graph.as_idx(v), graph.as_idx(u));
+                    let close = v != u
+                        && distance_squared(&graph.points, k, v_idx, u_idx, periodic)
+                            <= radius * radius;
+                    assert_eq!(graph.has_edge(v, u), close);
+                    m += usize::from(close);
+                }
+            }
+            assert_eq!(graph.m(), m / 2);
+        }
+    }
+    // the two points are 0.2 apart around the torus but 0.8 apart in the square
+    let points = Box::new([0.1, 0.5, 0.9, 0.5]);
+    let graph = GeometricGraph::from_points(2, 2, points.clone(), 0.3, false).unwrap();
+    assert_eq!(graph.m(), 0);
+    let graph = GeometricGraph::from_points(2, 2, points, 0.3, true).unwrap();
+    assert_eq!(graph.m(), 1);
+    let v = graph.iter_vertices().nth(1).unwrap();
+    assert_eq!(graph.position(v), [0.9, 0.5]);
+    // DOT output draws the vertices where they are given the layout
+    let layout = super::Layout::embedded(&graph);
+    let dot = super::BinGraph::<_>::new(graph)
+        .unwrap()
+        .with_layout(layout)
+        .to_string();
+    assert!(
+        dot.contains("\"1\" [label = \"1:0\", pos = \"0.9,0.5\"]"),
+        "{dot}"
+    );
+}
@@ -1,12 +1,16 @@
+use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
-use std::iter::repeat;
-use std::ops::{Deref, Index, IndexMut};
+use std::ops::Deref;
 
 use itertools::Itertools;
 use rand::Rng;
 
-pub type Bin = u32;
+use super::validation::{self, ValidationReport};
+use super::{BinStorage, NarrowBins};
+use crate::Error;
+
+pub type Bin = u64;
 
 pub trait Graph {
     type Vertex: Display + Copy + Hash + Eq;
@@ -14,10 +18,20 @@ pub trait Graph {
     type NIter: Iterator<Item = Self::Vertex>;
 
     fn n(&self) -> usize;
-    fn d(&self) -> usize;
+    // The degree of every vertex for regular graphs, None for irregular graphs
+    fn d(&self) -> Option<usize>;
+    // Irregular graphs must override this
+    fn degree(&self, v: Self::Vertex) -> usize {
+        let _ = v;
+        self.d().expect("irregular graphs must implement degree")
+    }
+    // Number of edges, irregular graphs should override this if it is cheaper than summing degrees
     fn m(&self) -> usize {
-        let m2 = self.n() * self.d();
-        debug_assert!(m2 % 2 == 0);
+        let m2 = match self.d() {
+            Some(d) => self.n() * d,
+            None => self.iter_vertices().map(|v| self.degree(v)).sum(),
+        };
+        debug_assert!(m2.is_multiple_of(2));
         m2 / 2
     }
 
@@ -26,37 +40,160 @@ pub trait Graph {
     fn iter_vertices(&self) -> Self::VIter;
     fn iter_neighbours(&self, v: Self::Vertex) -> Self::NIter;
     fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool;
+    // Returns an edge chosen uniformly at random from all edges, with a uniformly random orientation
+    // For regular graphs a random vertex and a random neighbour of it suffices
     fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex);
 
-    fn validate(&self) {
-        assert_eq!(self.iter_vertices().count(), self.n());
-        for v in self.iter_vertices() {
-            assert_eq!(self.iter_neighbours(v).count(), self.d());
-            for u in self.iter_neighbours(v) {
-                assert!(u != v);
-                assert!(self.iter_neighbours(u).any(|v2| v == v2));
-                assert_eq!(self.iter_neighbours(v).filter(|u2| u == *u2).count(), 1);
-                assert!(self.has_edge(v, u));
-                assert!(self.has_edge(u, v));
-            }
+    // Checks every invariant above, including that 'as_idx' is a bijection onto 0..n, and that
+    // 'random_edge' returns edges
+    fn validate(&self, rng: &mut impl Rng) -> ValidationReport {
+        validation::validate(self, rng)
+    }
+}
+
+// Graphs with a boundary, such as grids, whose vertices on it can have their loads reported
+// separately from those of the interior, see 'Layout::bounded'
+pub trait Bounded: Graph {
+    fn is_boundary(&self, v: Self::Vertex) -> bool;
+}
+
+// Graphs embedded in space, such as 'GeometricGraph', whose loads can be drawn where the vertices
+// are, see 'Layout::embedded'
+pub trait Embedded: Graph {
+    fn dimensions(&self) -> usize;
+    // The 'dimensions' coordinates of 'v'
+    fn position(&self, v: Self::Vertex) -> &[f64];
+}
+
+// What is known about the vertices of a graph beyond its edges, given to a 'BinGraph'
+// explicitly as most graphs know nothing of the sort
+// Vertices are looked up by index
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+    // whether each vertex is on the boundary, for graphs with one
+    boundary: Option<Box<[bool]>>,
+    // the coordinates of vertex idx are 'positions[idx * dimensions..(idx + 1) * dimensions]',
+    // for graphs embedded in space
+    positions: Option<Box<[f64]>>,
+    dimensions: usize,
+    // the sides of the torus whose vertices are written as their coordinates joined by '.'
+    sides: Option<Box<[usize]>>,
+    // the index in that torus of each vertex, once 'restrict' has taken a subgraph of it
+    original: Option<Box<[usize]>>,
+}
+
+impl Layout {
+    pub fn bounded<G: Bounded>(graph: &G) -> Self {
+        let mut boundary = vec![false; graph.n()];
+        for v in graph.iter_vertices() {
+            boundary[graph.as_idx(v)] = graph.is_boundary(v);
+        }
+        Layout {
+            boundary: Some(boundary.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn embedded<G: Embedded>(graph: &G) -> Self {
+        let k = graph.dimensions();
+        let mut positions = vec![0.0; graph.n() * k];
+        for v in graph.iter_vertices() {
+            let idx = graph.as_idx(v);
+            positions[idx * k..(idx + 1) * k].copy_from_slice(graph.position(v));
+        }
+        Layout {
+            positions: Some(positions.into()),
+            dimensions: k,
+            ..Default::default()
+        }
+    }
+
+    // Vertices written as their coordinates below 'sides', see 'strides', as tori always have been
+    pub fn coordinates(sides: &[usize]) -> Self {
+        Layout {
+            sides: Some(sides.into()),
+            ..Default::default()
         }
-        let mut rng = rand::thread_rng();
-        for _ in 0..100 {
-            let (u, v) = self.random_edge(&mut rng);
-            assert!(self.iter_neighbours(u).any(|v2| v == v2), "{u}, {v}");
-            assert!(self.has_edge(u, v));
-            assert!(self.has_edge(v, u));
+    }
+
+    // Whether the vertex with index 'idx' is on the boundary, which no vertex is without one
+    pub fn is_boundary(&self, idx: usize) -> bool {
+        self.boundary.as_ref().is_some_and(|boundary| boundary[idx])
+    }
+
+    // The layout of the subgraph on the vertices with indexes 'kept', in that order
+    pub fn restrict(&self, kept: &[usize]) -> Self {
+        let k = self.dimensions;
+        Layout {
+            boundary: self
+                .boundary
+                .as_ref()
+                .map(|boundary| kept.iter().map(|&idx| boundary[idx]).collect()),
+            positions: self.positions.as_ref().map(|positions| {
+                kept.iter()
+                    .flat_map(|&idx| &positions[idx * k..(idx + 1) * k])
+                    .copied()
+                    .collect()
+            }),
+            dimensions: k,
+            sides: self.sides.clone(),
+            original: self.sides.as_ref().map(|_| {
+                kept.iter()
+                    .map(|&idx| self.original.as_ref().map_or(idx, |original| original[idx]))
+                    .collect()
+            }),
         }
     }
+
+    // The coordinates of the vertex with index 'idx', for graphs embedded in space
+    pub fn position(&self, idx: usize) -> Option<&[f64]> {
+        let k = self.dimensions;
+        let positions = self.positions.as_ref()?;
+        Some(&positions[idx * k..(idx + 1) * k])
+    }
+
+    // How the vertex with index 'idx' is written in output, None if as itself
+    pub fn name(&self, idx: usize) -> Option<String> {
+        let sides = self.sides.as_ref()?;
+        let mut idx = self.original.as_ref().map_or(idx, |original| original[idx]);
+        let mut coordinates = vec![0; sides.len()];
+        for (c, &side) in coordinates.iter_mut().zip(sides.iter()).rev() {
+            (*c, idx) = (idx % side, idx / side);
+        }
+        Some(coordinates.iter().join("."))
+    }
+
+    // Whether this could be the layout of a graph on 'n' vertices
+    fn fits(&self, n: usize) -> bool {
+        self.boundary
+            .as_ref()
+            .is_none_or(|boundary| boundary.len() == n)
+            && self
+                .positions
+                .as_ref()
+                .is_none_or(|positions| positions.len() == n * self.dimensions)
+            && match (&self.sides, &self.original) {
+                (_, Some(original)) => original.len() == n,
+                (Some(sides), None) => sides.iter().product::<usize>() == n,
+                (None, None) => true,
+            }
+    }
 }
 
 // Wraps a graph by attaching bins to each of it's vertices
-pub struct BinGraph<G: Graph> {
+// Also maintains a histogram of the loads so that summary statistics are O(1)
+// 'S' selects how the loads are stored, see 'BinStorage'
+pub struct BinGraph<G: Graph, S: BinStorage = NarrowBins> {
     graph: G,
-    data: Box<[Bin]>,
+    data: S,
+    min: Bin,
+    // counts[i] is the number of bins containing 'min + i' balls, the last entry is never 0
+    counts: VecDeque<usize>,
+    total: u64,
+    layout: Layout,
 }
 
-impl<G: Graph> Deref for BinGraph<G> {
+impl<G: Graph, S: BinStorage> Deref for BinGraph<G, S> {
     type Target = G;
 
     fn deref(&self) -> &Self::Target {
@@ -64,63 +201,247 @@ impl<G: Graph> Deref for BinGraph<G> {
     }
 }
 
-impl<G: Graph> Index<G::Vertex> for BinGraph<G> {
-    type Output = Bin;
+impl<G: Graph, S: BinStorage> BinGraph<G, S> {
+    // Fails if balls cannot be thrown into 'graph', so 'new' would fail
+    pub fn check(graph: &G) -> Result<(), Error> {
+        if graph.n() == 0 {
+            Err(Error::NoVertices)
+        } else if graph.m() == 0 {
+            Err(Error::NoEdges)
+        } else {
+            Ok(())
+        }
+    }
 
-    fn index(&self, index: G::Vertex) -> &Self::Output {
-        &self.data[self.as_idx(index)]
+    pub fn new(graph: G) -> Result<Self, Error> {
+        Self::check(&graph)?;
+        let n = graph.n();
+        Ok(BinGraph {
+            graph,
+            data: S::new(n),
+            min: 0,
+            counts: VecDeque::from([n]),
+            total: 0,
+            layout: Layout::default(),
+        })
     }
-}
 
-impl<G: Graph> IndexMut<G::Vertex> for BinGraph<G> {
-    fn index_mut(&mut self, index: G::Vertex) -> &mut Self::Output {
-        &mut self.data[self.as_idx(index)]
+    // Attaches 'layout', which must be one of this graph, for 'region_loads' and output to use
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        assert!(layout.fits(self.n()), "layout is for a different graph");
+        self.layout = layout;
+        self
     }
-}
 
-impl<G: Graph> BinGraph<G> {
-    pub fn new(graph: G) -> Self {
-        let n = graph.n();
-        BinGraph {
-            graph,
-            data: repeat(0).take(n).collect(),
+    // How 'v' is written in output, see 'Layout::name'
+    pub fn name(&self, v: G::Vertex) -> String {
+        self.layout
+            .name(self.as_idx(v))
+            .unwrap_or_else(|| v.to_string())
+    }
+
+    // Number of balls in the bin at 'v'
+    pub fn load(&self, v: G::Vertex) -> Bin {
+        self.data.get(self.as_idx(v))
+    }
+
+    // Throws a ball into the bin at 'v'
+    pub fn add_ball(&mut self, v: G::Vertex) {
+        let idx = self.as_idx(v);
+        let rel = (self.data.get(idx) - self.min) as usize;
+        self.data.increment(idx, self.min);
+        self.total += 1;
+        self.counts[rel] -= 1;
+        if rel + 1 == self.counts.len() {
+            self.counts.push_back(0)
+        }
+        self.counts[rel + 1] += 1;
+        if rel == 0 && self.counts[0] == 0 {
+            self.counts.pop_front();
+            self.min += 1;
         }
     }
 
+    pub fn total_load(&self) -> u64 {
+        self.total
+    }
+
+    pub fn average_load(&self) -> f64 {
+        self.total as f64 / self.n() as f64
+    }
+
+    pub fn max_load(&self) -> Bin {
+        self.min + self.gap()
+    }
+
+    pub fn min_load(&self) -> Bin {
+        self.min
+    }
+
+    // Iterates over each load between the minimum and maximum and the number of bins with it
+    pub fn load_counts(&self) -> impl Iterator<Item = (Bin, usize)> + '_ {
+        (self.min..).zip(self.counts.iter().copied())
+    }
+
     pub fn gap(&self) -> Bin {
-        let minmax = self.iter_vertices().map(|v| self[v]).minmax();
-        let (min, max) = minmax.into_option().unwrap();
-        max - min
+        (self.counts.len() - 1) as Bin
     }
 
     pub fn upper_gap(&self) -> Bin {
-        let (sum, max) = self
+        self.max_load() - self.total / self.n() as u64
+    }
+
+    // The loads of the boundary vertices if 'boundary', otherwise of the interior vertices, or
+    // None if there are no such vertices
+    // Every vertex is in the interior unless the layout says otherwise, see 'with_layout'
+    // Unlike the other statistics this scans every bin
+    pub fn region_loads(&self, boundary: bool) -> Option<RegionLoads> {
+        let loads = self
             .iter_vertices()
-            .map(|v| self[v])
-            .fold((0u64, 0u32), |(sum, max), x| (sum + x as u64, max.max(x)));
-        let avg = sum / self.n() as u64;
-        max - avg as Bin
+            .filter(|&v| self.layout.is_boundary(self.as_idx(v)) == boundary)
+            .map(|v| self.load(v));
+        let (mut vertices, mut min_load, mut max_load, mut total) = (0, Bin::MAX, 0, 0);
+        for load in loads {
+            vertices += 1;
+            min_load = min_load.min(load);
+            max_load = max_load.max(load);
+            total += load;
+        }
+        (vertices > 0).then(|| RegionLoads {
+            vertices,
+            min_load,
+            max_load,
+            average_load: total as f64 / vertices as f64,
+        })
     }
 }
 
-impl<G: Graph> Display for BinGraph<G> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+// Summary of the loads of some of the vertices of a 'BinGraph', see 'region_loads'
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RegionLoads {
+    pub vertices: usize,
+    pub min_load: Bin,
+    pub max_load: Bin,
+    pub average_load: f64,
+}
+
+impl<G: Graph, S: BinStorage> BinGraph<G, S> {
+    // Writes the graph in DOT format, appending 'extra_label' to the graph's label
+    pub fn write_dot(&self, f: &mut Formatter<'_>, extra_label: &str) -> std::fmt::Result {
         writeln!(f, "strict graph {{")?;
         writeln!(
             f,
-            "label = \"n = {}, d = {}, gap = {}, upper_gap = {}\"",
+            "label = \"n = {}, d = {}, gap = {}, upper_gap = {}{extra_label}\"",
             self.n(),
-            self.d(),
+            self.d().map_or("irregular".to_string(), |d| d.to_string()),
             self.gap(),
             self.upper_gap()
         )?;
+        // vertices are quoted as the vertices of combined graphs, such as 0x1, are not DOT ids
         for v in self.iter_vertices() {
-            writeln!(f, "  {v} [label = \"{v}:{balls}\"]", balls = self[v])?;
+            let name = self.name(v);
+            write!(
+                f,
+                "  \"{name}\" [label = \"{name}:{balls}\"",
+                balls = self.load(v)
+            )?;
+            if let Some(position) = self.layout.position(self.as_idx(v)) {
+                write!(f, ", pos = \"{}\"", position.iter().join(","))?;
+            }
+            writeln!(f, "]")?;
             for u in self.iter_neighbours(v) {
-                writeln!(f, "  {v} -- {u}")?;
+                writeln!(f, "  \"{name}\" -- \"{}\"", self.name(u))?;
             }
         }
         writeln!(f, "}}")?;
         Ok(())
     }
 }
+
+impl<G: Graph, S: BinStorage> Display for BinGraph<G, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_dot(f, "")
+    }
+}
+
+#[test]
+fn test_histogram() {
+    use super::RingGraph;
+
+    let mut rng = crate::rng::seeded(0);
+    let mut graph: BinGraph<_> = BinGraph::new(RingGraph { n: 13 }).unwrap();
+    for _ in 0..2000 {
+        // skew the loads to exercise large gaps as well as small ones
+        let bound = rng.gen_range(1..=13);
+        let v = graph.iter_vertices().nth(rng.gen_range(0..bound)).unwrap();
+        graph.add_ball(v);
+        let loads: Vec<Bin> = graph.iter_vertices().map(|v| graph.load(v)).collect();
+        let (min, max) = (*loads.iter().min().unwrap(), *loads.iter().max().unwrap());
+        let sum: u64 = loads.iter().sum();
+        assert_eq!((graph.min_load(), graph.max_load()), (min, max));
+        assert_eq!((graph.gap(), graph.total_load()), (max - min, sum));
+        assert_eq!(graph.upper_gap(), max - sum / 13);
+        for (load, count) in graph.load_counts() {
+            assert_eq!(loads.iter().filter(|&&x| x == load).count(), count);
+        }
+    }
+}
+
+#[test]
+fn test_invalid_bins() {
+    use super::{CsrGraph, HyperCubeGraph};
+
+    let empty = CsrGraph::from_edges(0, &[]).unwrap();
+    assert_eq!(BinGraph::<_>::new(empty).err(), Some(Error::NoVertices));
+    let point = HyperCubeGraph::new(0).unwrap();
+    assert_eq!(BinGraph::<_>::new(point).err(), Some(Error::NoEdges));
+}
+
+#[test]
+fn test_region_loads() {
+    use super::{GridGraph, RingGraph};
+
+    // a 3 x 3 grid has only its centre, vertex 4, in the interior
+    let grid = GridGraph::new(&[3, 3]).unwrap();
+    let layout = Layout::bounded(&grid);
+    let mut graph: BinGraph<_> = BinGraph::new(grid).unwrap().with_layout(layout);
+    for v in graph.iter_vertices().collect::<Vec<_>>() {
+        for _ in 0..graph.as_idx(v) + 1 {
+            graph.add_ball(v);
+        }
+    }
+    let interior = graph.region_loads(false).unwrap();
+    assert_eq!(
+        (interior.vertices, interior.min_load, interior.max_load),
+        (1, 5, 5)
+    );
+    let boundary = graph.region_loads(true).unwrap();
+    assert_eq!(
+        (boundary.vertices, boundary.min_load, boundary.max_load),
+        (8, 1, 9)
+    );
+    assert_eq!(boundary.average_load, 40.0 / 8.0);
+    // graphs without a boundary are all interior
+    let ring: BinGraph<_> = BinGraph::new(RingGraph { n: 5 }).unwrap();
+    assert_eq!(ring.region_loads(false).unwrap().vertices, 5);
+    assert_eq!(ring.region_loads(true), None);
+    // so are grids without their layout
+    let grid: BinGraph<_> = BinGraph::new(GridGraph::new(&[3, 3]).unwrap()).unwrap();
+    assert_eq!(grid.region_loads(true), None);
+}
+
+#[test]
+fn test_layout_names() {
+    use super::TorusGraph;
+
+    // torus vertices are written as their coordinates, also in subgraphs
+    let layout = Layout::coordinates(&[3, 4]);
+    let graph: BinGraph<_> = BinGraph::new(TorusGraph::new(&[3, 4]).unwrap())
+        .unwrap()
+        .with_layout(layout.clone());
+    let v = graph.iter_vertices().nth(6).unwrap();
+    assert_eq!(graph.name(v), "1.2");
+    assert!(graph.to_string().contains("\"1.2\" -- \"1.3\""));
+    assert_eq!(layout.restrict(&[11, 6]).name(1), Some("1.2".to_string()));
+    assert_eq!(Layout::default().name(6), None);
+}
@@ -0,0 +1,265 @@
+use crate::graph::{CsrGraph, Graph};
+
+// Readers and writers for nauty's graph6 and sparse6 formats, see
+// https://users.cecs.anu.edu.au/~bdm/data/formats.txt
+// Both formats pack 6 bits into each printable byte from '?' (63) to '~' (126)
+
+const BIAS: u8 = 63;
+
+// The graph encoded by a graph6 or sparse6 line, without the optional header or trailing
+// whitespace, which are part of the file rather than the graph
+pub fn graph6_code(line: &str) -> &str {
+    let line = line.trim_end();
+    let line = line.strip_prefix(">>graph6<<").unwrap_or(line);
+    line.strip_prefix(">>sparse6<<").unwrap_or(line)
+}
+
+// Parses a single graph6 or sparse6 line, sparse6 lines are recognised by their leading ':'
+pub fn parse_graph6(line: &str) -> Result<CsrGraph, String> {
+    let line = graph6_code(line);
+    match line.strip_prefix(':') {
+        Some(sparse) => parse_sparse6(sparse.as_bytes()),
+        None => parse_dense6(line.as_bytes()),
+    }
+    .map_err(|err| format!("invalid graph6 '{line}': {err}"))
+}
+
+// Encodes 'graph' in graph6 format, vertex i being the vertex with index i
+pub fn to_graph6<G: Graph>(graph: &G) -> String {
+    let graph = CsrGraph::from_graph(graph);
+    let vertices: Vec<_> = graph.iter_vertices().collect();
+    let mut bits = BitWriter::new(vec![]);
+    write_size(&mut bits.out, graph.n());
+    for (j, &v) in vertices.iter().enumerate() {
+        for &u in &vertices[..j] {
+            bits.push(graph.has_edge(u, v));
+        }
+    }
+    bits.finish(false)
+}
+
+// Encodes 'graph' in sparse6 format, which is much shorter than graph6 for sparse graphs
+pub fn to_sparse6<G: Graph>(graph: &G) -> String {
+    let graph = CsrGraph::from_graph(graph);
+    let n = graph.n();
+    let k = index_bits(n);
+    let mut bits = BitWriter::new(vec![b':']);
+    write_size(&mut bits.out, n);
+    // each edge is written from its larger endpoint j, the decoder tracks the current j in 'v'
+    let mut v = 0;
+    for j in graph.iter_vertices() {
+        let j_idx = graph.as_idx(j);
+        for i in graph.iter_neighbours(j).map(|i| graph.as_idx(i)) {
+            if i > j_idx {
+                continue;
+            } else if j_idx == v {
+                bits.push(false);
+            } else if j_idx == v + 1 {
+                bits.push(true);
+            } else {
+                bits.push(true);
+                bits.push_int(j_idx, k);
+                bits.push(false);
+            }
+            bits.push_int(i, k);
+            v = j_idx;
+        }
+    }
+    // padding with ones would read as the loop at n - 1 in this case, so start it with a zero
+    if k < 6 && n == 1 << k && v + 2 == n && bits.padding() > k {
+        bits.push(false);
+    }
+    bits.finish(true)
+}
+
+// Number of bits needed to write a vertex index in sparse6
+fn index_bits(n: usize) -> usize {
+    (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+}
+
+fn parse_dense6(bytes: &[u8]) -> Result<CsrGraph, String> {
+    let (n, rest) = read_size(bytes)?;
+    let pairs = n * n.saturating_sub(1) / 2;
+    if rest.len() != pairs.div_ceil(6) {
+        return Err(format!(
+            "expected {} bytes of edges for {n} vertices, found {}",
+            pairs.div_ceil(6),
+            rest.len()
+        ));
+    }
+    let mut bits = BitReader::new(rest)?;
+    let mut edges = vec![];
+    for j in 0..n {
+        for i in 0..j {
+            if bits.read() {
+                edges.push((i, j));
+            }
+        }
+    }
+    CsrGraph::from_edges(n, &edges).map_err(|err| err.to_string())
+}
+
+fn parse_sparse6(bytes: &[u8]) -> Result<CsrGraph, String> {
+    let (n, rest) = read_size(bytes)?;
+    let k = index_bits(n);
+    let mut bits = BitReader::new(rest)?;
+    let mut edges = vec![];
+    let mut v = 0;
+    while bits.remaining() > k {
+        if bits.read() {
+            v += 1;
+        }
+        let x = bits.read_int(k);
+        if x > v {
+            v = x;
+        } else if v < n {
+            edges.push((x, v));
+        }
+    }
+    CsrGraph::from_edges(n, &edges).map_err(|err| err.to_string())
+}
+
+fn write_size(out: &mut Vec<u8>, n: usize) {
+    assert!(n < 1 << 36, "graph is too large for graph6");
+    let (prefix, width): (&[u8], _) = match n {
+        0..63 => (&[], 1),
+        63..0x40000 => (&[126], 3),
+        _ => (&[126, 126], 6),
+    };
+    out.extend_from_slice(prefix);
+    out.extend((0..width).rev().map(|i| (n >> (6 * i)) as u8 % 64 + BIAS));
+}
+
+fn read_size(bytes: &[u8]) -> Result<(usize, &[u8]), String> {
+    let (width, rest) = match bytes {
+        [126, 126, rest @ ..] => (6, rest),
+        [126, rest @ ..] => (3, rest),
+        _ => (1, bytes),
+    };
+    if rest.len() < width {
+        return Err("missing number of vertices".to_string());
+    }
+    let n = rest[..width]
+        .iter()
+        .try_fold(0, |n, &b| Ok::<_, String>(n << 6 | six_bits(b)? as usize))?;
+    Ok((n, &rest[width..]))
+}
+
+fn six_bits(b: u8) -> Result<u8, String> {
+    match b {
+        63..=126 => Ok(b - BIAS),
+        _ => Err(format!("invalid character '{}'", b.escape_ascii())),
+    }
+}
+
+// Packs bits into bytes of 6 bits each, most significant bit first
+struct BitWriter {
+    out: Vec<u8>,
+    cur: u8,
+    len: usize,
+}
+
+impl BitWriter {
+    fn new(out: Vec<u8>) -> Self {
+        BitWriter {
+            out,
+            cur: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bit: bool) {
+        self.cur = self.cur << 1 | bit as u8;
+        self.len += 1;
+        if self.len == 6 {
+            self.out.push(self.cur + BIAS);
+            self.cur = 0;
+            self.len = 0;
+        }
+    }
+
+    fn push_int(&mut self, x: usize, bits: usize) {
+        for i in (0..bits).rev() {
+            self.push(x >> i & 1 == 1)
+        }
+    }
+
+    // Number of bits needed to complete the last byte
+    fn padding(&self) -> usize {
+        (6 - self.len) % 6
+    }
+
+    fn finish(mut self, fill: bool) -> String {
+        while self.len != 0 {
+            self.push(fill)
+        }
+        String::from_utf8(self.out).expect("graph6 is printable ASCII")
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self, String> {
+        bytes.iter().try_for_each(|&b| six_bits(b).map(|_| ()))?;
+        Ok(BitReader { bytes, pos: 0 })
+    }
+
+    fn remaining(&self) -> usize {
+        6 * self.bytes.len() - self.pos
+    }
+
+    fn read(&mut self) -> bool {
+        let (byte, shift) = (self.bytes[self.pos / 6] - BIAS, 5 - self.pos % 6);
+        self.pos += 1;
+        byte >> shift & 1 == 1
+    }
+
+    fn read_int(&mut self, bits: usize) -> usize {
+        (0..bits).fold(0, |x, _| x << 1 | self.read() as usize)
+    }
+}
+
+#[test]
+fn test_known_graphs() {
+    use super::{FullGraph, RingGraph};
+
+    // examples from the format description
+    let graph = parse_graph6("DQc").unwrap();
+    assert_eq!((graph.n(), graph.m()), (5, 4));
+    let graph = parse_graph6(":Fa@x^").unwrap();
+    assert_eq!((graph.n(), graph.m()), (7, 4));
+    assert_eq!(to_sparse6(&graph), ":Fa@x^");
+
+    assert_eq!(to_graph6(&FullGraph::new(4).unwrap()), "C~");
+    assert_eq!(to_graph6(&RingGraph::new(5).unwrap()), "Dhc");
+    assert_eq!(parse_graph6(">>graph6<<C~").unwrap().d(), Some(3));
+    assert!(parse_graph6("C~~").is_err());
+    assert!(parse_graph6("C ").is_err());
+    assert!(parse_graph6("").is_err());
+}
+
+#[test]
+fn test_round_trip() {
+    use super::{HyperCubeGraph, StarGraph, TorusGraph};
+
+    let mut rng = crate::rng::seeded(0);
+    let graphs = [
+        CsrGraph::from_edges(0, &[]).unwrap(),
+        CsrGraph::from_edges(1, &[]).unwrap(),
+        CsrGraph::from_graph(&HyperCubeGraph::new(3).unwrap()),
+        CsrGraph::from_graph(&StarGraph::new(9).unwrap()),
+        CsrGraph::from_graph(&TorusGraph::new(&[10, 10]).unwrap()),
+        CsrGraph::from_graph(&super::ArbitraryGraph::random(100, 3, &mut rng).unwrap()),
+        // the cases where sparse6 padding needs a leading zero
+        CsrGraph::from_edges(4, &[(0, 1), (0, 2), (1, 2)]).unwrap(),
+        CsrGraph::from_edges(8, &[(5, 6)]).unwrap(),
+    ];
+    for graph in graphs {
+        assert_eq!(parse_graph6(&to_graph6(&graph)).unwrap(), graph);
+        assert_eq!(parse_graph6(&to_sparse6(&graph)).unwrap(), graph);
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::torus_graph::strides;
+use super::{Bounded, Graph};
+use crate::Error;
+
+// The k-dimensional grid with the given side lengths, like 'TorusGraph' but without wrapping
+// around, so vertices on the boundary have fewer neighbours
+// Each vertex is its index, see 'strides'
+pub struct GridGraph {
+    sides: Box<[usize]>,
+    strides: Box<[usize]>,
+    n: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct GridVertex(usize);
+
+impl Display for GridVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for GridGraph {
+    type Vertex = GridVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> GridVertex>;
+    type NIter = smallvec::IntoIter<[GridVertex; 6]>;
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn d(&self) -> Option<usize> {
+        // only grids without interior vertices are regular
+        if self.sides.iter().all(|&side| side <= 2) {
+            Some(self.sides.iter().filter(|&&side| side == 2).count())
+        } else {
+            None
+        }
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        (0..self.sides.len())
+            .map(|dim| {
+                let c = self.coordinate(v, dim);
+                usize::from(c > 0) + usize::from(c + 1 < self.sides[dim])
+            })
+            .sum()
+    }
+
+    fn m(&self) -> usize {
+        // each dimension has a path of side - 1 edges for every value of the other coordinates
+        self.sides
+            .iter()
+            .map(|&side| (side - 1) * (self.n / side))
+            .sum()
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n).map(GridVertex)
+    }
+
+    fn iter_neighbours(&self, v: GridVertex) -> Self::NIter {
+        let mut res = SmallVec::new();
+        for (dim, (&side, &stride)) in self.sides.iter().zip(self.strides.iter()).enumerate() {
+            let c = self.coordinate(v, dim);
+            if c > 0 {
+                res.push(GridVertex(v.0 - stride));
+            }
+            if c + 1 < side {
+                res.push(GridVertex(v.0 + stride));
+            }
+        }
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        // neighbours differ by one in exactly one coordinate
+        let mut differing =
+            (0..self.sides.len()).filter(|&dim| self.coordinate(v, dim) != self.coordinate(u, dim));
+        match (differing.next(), differing.next()) {
+            (Some(dim), None) => self.coordinate(v, dim).abs_diff(self.coordinate(u, dim)) == 1,
+            _ => false,
+        }
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // the edges along each dimension in turn, those along 'dim' being numbered by the index
+        // of their lower end with coordinate 'dim' only counting up to side - 2
+        let mut edge = rng.gen_range(0..self.m());
+        for (&side, &stride) in self.sides.iter().zip(self.strides.iter()) {
+            let count = (side - 1) * (self.n / side);
+            if edge < count {
+                let (high, low) = (edge / stride, edge % stride);
+                let (high, c) = (high / (side - 1), high % (side - 1));
+                let v = GridVertex((high * side + c) * stride + low);
+                let u = GridVertex(v.0 + stride);
+                return if rng.gen() { (v, u) } else { (u, v) };
+            }
+            edge -= count;
+        }
+        unreachable!()
+    }
+}
+
+impl Bounded for GridGraph {
+    fn is_boundary(&self, v: Self::Vertex) -> bool {
+        // dimensions with a single coordinate have no edges, so no boundary
+        (0..self.sides.len()).any(|dim| {
+            let c = self.coordinate(v, dim);
+            self.sides[dim] > 1 && (c == 0 || c + 1 == self.sides[dim])
+        })
+    }
+}
+
+impl GridGraph {
+    pub fn new(sides: &[usize]) -> Result<Self, Error> {
+        let (strides, n) = strides(sides)?;
+        Ok(GridGraph {
+            sides: sides.into(),
+            strides,
+            n,
+        })
+    }
+
+    pub fn sides(&self) -> &[usize] {
+        &self.sides
+    }
+
+    // The coordinates of 'v', one per side
+    pub fn coordinates(&self, v: GridVertex) -> Vec<usize> {
+        (0..self.sides.len())
+            .map(|dim| self.coordinate(v, dim))
+            .collect()
+    }
+
+    fn coordinate(&self, v: GridVertex, dim: usize) -> usize {
+        v.0 / self.strides[dim] % self.sides[dim]
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for sides in [
+        &[2, 1][..],
+        &[1, 3],
+        &[2, 5],
+        &[7, 2],
+        &[10, 10],
+        &[3, 5],
+        &[4],
+        &[3, 4, 5],
+        &[2, 2, 2],
+        &[1, 1, 6, 1, 3],
+    ] {
+        GridGraph::new(sides)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(GridGraph::new(&[3, 0]).err(), Some(Error::NoVertices));
+}
+
+#[test]
+fn test_structure() {
+    let graph = GridGraph::new(&[3, 4]).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (12, None, 17));
+    let degrees: Vec<usize> = graph.iter_vertices().map(|v| graph.degree(v)).collect();
+    assert_eq!(degrees, [2, 3, 3, 2, 3, 4, 4, 3, 2, 3, 3, 2]);
+    let interior: Vec<usize> = graph
+        .iter_vertices()
+        .filter(|&v| !graph.is_boundary(v))
+        .map(|v| v.0)
+        .collect();
+    assert_eq!(interior, [5, 6]);
+    assert_eq!(graph.coordinates(GridVertex(6)), [1, 2]);
+    // a 2 x 2 x 2 grid is a cube, every vertex being on the boundary
+    let graph = GridGraph::new(&[2, 2, 2]).unwrap();
+    assert_eq!((graph.d(), graph.m()), (Some(3), 12));
+    assert!(graph.iter_vertices().all(|v| graph.is_boundary(v)));
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    // edges along the short side are less numerous than those along the long side
+    let graph = GridGraph::new(&[2, 5]).unwrap();
+    let mut rng = crate::rng::seeded(1);
+    let mut counts = std::collections::HashMap::new();
+    for _ in 0..26000 {
+        let (u, v) = graph.random_edge(&mut rng);
+        *counts.entry((u.0.min(v.0), u.0.max(v.0))).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), graph.m());
+    assert!(
+        counts.values().all(|&c| (1800..2200).contains(&c)),
+        "{counts:?}"
+    );
+}
@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::vec::IntoIter;
+
+use rand::Rng;
+
+use crate::graph::Graph;
+use crate::Error;
+
+// The words of length d over an alphabet of size q, joined when they differ in exactly one letter
+// H(d, 2) is the d-dimensional hypercube and H(1, q) the complete graph on q vertices
+// Each vertex is its index, whose digits in base q are the letters of the word
+pub struct HammingGraph {
+    d: usize,
+    q: usize,
+    // powers[i] is q^i, for i up to d
+    powers: Box<[usize]>,
+    // log[b] is the largest i with q^i below 2^b, so floor(log_q(x)) for x with b bits is either
+    // log[b] or log[b] - 1
+    log: Box<[usize]>,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct HammingVertex(usize);
+
+impl Display for HammingVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for HammingGraph {
+    type Vertex = HammingVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> HammingVertex>;
+    type NIter = IntoIter<HammingVertex>;
+
+    fn n(&self) -> usize {
+        self.powers[self.d]
+    }
+
+    fn d(&self) -> Option<usize> {
+        Some(self.d * (self.q - 1))
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(HammingVertex)
+    }
+
+    fn iter_neighbours(&self, v: HammingVertex) -> Self::NIter {
+        let mut res = Vec::with_capacity(self.d * (self.q - 1));
+        for i in 0..self.d {
+            let letter = self.letter(v, i);
+            res.extend(
+                (0..self.q)
+                    .filter(|&x| x != letter)
+                    .map(|x| self.replaced(v, i, x)),
+            );
+        }
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        // if only letter i differs then |v - u| is below q^(i + 1) but at least q^i, and the
+        // letters above and below i agree
+        let diff = v.0.abs_diff(u.0);
+        if diff == 0 {
+            return false;
+        }
+        let i = self.log[(usize::BITS - diff.leading_zeros()) as usize];
+        let i = if self.powers[i] > diff { i - 1 } else { i };
+        v.0 / self.powers[i + 1] == u.0 / self.powers[i + 1]
+            && v.0 % self.powers[i] == u.0 % self.powers[i]
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // a graph with edges has d >= 1 and q >= 2, so both ranges below are non-empty
+        let v = HammingVertex(rng.gen_range(0..self.n()));
+        let i = rng.gen_range(0..self.d);
+        let letter = self.letter(v, i);
+        // a uniformly random letter other than the current one
+        let x = rng.gen_range(0..self.q - 1);
+        let x = if x >= letter { x + 1 } else { x };
+        (v, self.replaced(v, i, x))
+    }
+}
+
+impl HammingGraph {
+    pub fn new(d: usize, q: usize) -> Result<Self, Error> {
+        // without letters there are no words, even the empty word of length 0
+        if q == 0 {
+            return Err(Error::NoVertices);
+        }
+        let mut powers: Vec<usize> = vec![1];
+        for i in 0..d {
+            powers.push(powers[i].checked_mul(q).ok_or(Error::TooManyVertices)?);
+        }
+        let log = (0..=usize::BITS)
+            .map(|b| {
+                powers
+                    .iter()
+                    .rposition(|&p| b == usize::BITS || p < 1 << b)
+                    .unwrap_or(0)
+            })
+            .collect();
+        Ok(HammingGraph {
+            d,
+            q,
+            powers: powers.into_boxed_slice(),
+            log,
+        })
+    }
+
+    // The i'th letter of 'v', i = 0 being the least significant digit of its index
+    pub fn letter(&self, v: HammingVertex, i: usize) -> usize {
+        v.0 / self.powers[i] % self.q
+    }
+
+    // 'v' with letter i replaced by 'x'
+    fn replaced(&self, v: HammingVertex, i: usize, x: usize) -> HammingVertex {
+        HammingVertex(v.0 - self.letter(v, i) * self.powers[i] + x * self.powers[i])
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for (d, q) in [
+        (1, 2),
+        (1, 5),
+        (2, 3),
+        (3, 4),
+        (4, 2),
+        (2, 7),
+        (0, 3),
+        (3, 1),
+    ] {
+        HammingGraph::new(d, q)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(HammingGraph::new(2, 0).err(), Some(Error::NoVertices));
+    assert_eq!(HammingGraph::new(0, 0).err(), Some(Error::NoVertices));
+    assert_eq!(HammingGraph::new(65, 2).err(), Some(Error::TooManyVertices));
+}
+
+#[test]
+fn test_special_cases() {
+    use super::{CsrGraph, FullGraph, HyperCubeGraph};
+
+    let csr = |graph: &HammingGraph| CsrGraph::from_graph(graph);
+    assert_eq!(
+        csr(&HammingGraph::new(5, 2).unwrap()),
+        CsrGraph::from_graph(&HyperCubeGraph::new(5).unwrap())
+    );
+    assert_eq!(
+        csr(&HammingGraph::new(1, 6).unwrap()),
+        CsrGraph::from_graph(&FullGraph::new(6).unwrap())
+    );
+}
+
+#[test]
+fn test_has_edge() {
+    // compare against the definition, including pairs whose difference is a multiple of a power
+    // of q because of a borrow, like 10 and 9 in base 10
+    let graph = HammingGraph::new(3, 10).unwrap();
+    for v in graph.iter_vertices() {
+        for u in graph.iter_vertices() {
+            let differing = (0..3)
+                .filter(|&i| graph.letter(v, i) != graph.letter(u, i))
+                .count();
+            assert_eq!(graph.has_edge(v, u), differing == 1, "{v}, {u}");
+        }
+    }
+}
@@ -1,13 +1,14 @@
 use std::fmt::{Display, Formatter};
-use std::iter::{Enumerate, Map, Repeat, Take};
+use std::iter::{Enumerate, Map, RepeatN};
 use std::ops::Range;
 
 use rand::Rng;
 
 use crate::graph::Graph;
+use crate::Error;
 
 pub struct HyperCubeGraph {
-    pub d: u8,
+    d: u8,
 }
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
@@ -28,17 +29,15 @@ impl Display for HyperCubeVertex {
 impl Graph for HyperCubeGraph {
     type Vertex = HyperCubeVertex;
     type VIter = std::iter::Map<Range<usize>, fn(usize) -> HyperCubeVertex>;
-    type NIter = Map<
-        Enumerate<Take<Repeat<HyperCubeVertex>>>,
-        fn((usize, HyperCubeVertex)) -> HyperCubeVertex,
-    >;
+    type NIter =
+        Map<Enumerate<RepeatN<HyperCubeVertex>>, fn((usize, HyperCubeVertex)) -> HyperCubeVertex>;
 
     fn n(&self) -> usize {
         1 << self.d
     }
 
-    fn d(&self) -> usize {
-        self.d as usize
+    fn d(&self) -> Option<usize> {
+        Some(self.d as usize)
     }
 
     fn as_idx(&self, v: Self::Vertex) -> usize {
@@ -46,12 +45,11 @@ impl Graph for HyperCubeGraph {
     }
 
     fn iter_vertices(&self) -> Self::VIter {
-        (0..self.n()).map(|x| HyperCubeVertex(x))
+        (0..self.n()).map(HyperCubeVertex)
     }
 
     fn iter_neighbours(&self, v: HyperCubeVertex) -> Self::NIter {
-        std::iter::repeat(v)
-            .take(self.d())
+        std::iter::repeat_n(v, self.d as usize)
             .enumerate()
             .map(|(i, v)| v.flip(i as u8))
     }
@@ -63,14 +61,36 @@ impl Graph for HyperCubeGraph {
     fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
         let v = HyperCubeVertex(rng.gen_range(0..self.n()));
         let bit = rng.gen_range(0..self.d);
-        (v, v.flip(bit as u8))
+        (v, v.flip(bit))
+    }
+}
+
+impl HyperCubeGraph {
+    pub fn new(d: u8) -> Result<Self, Error> {
+        // n * d, twice the number of edges, must fit too
+        let n = 1usize.checked_shl(d.into()).filter(|&n| n != 0);
+        if n.and_then(|n| n.checked_mul(d.into())).is_none() {
+            Err(Error::TooManyVertices)
+        } else {
+            Ok(HyperCubeGraph { d })
+        }
     }
 }
 
 #[test]
 fn test_valid() {
-    HyperCubeGraph { d: 1 }.validate();
-    HyperCubeGraph { d: 2 }.validate();
-    HyperCubeGraph { d: 3 }.validate();
-    HyperCubeGraph { d: 5 }.validate();
+    let mut rng = crate::rng::seeded(0);
+    HyperCubeGraph { d: 1 }.validate(&mut rng).assert_valid();
+    HyperCubeGraph { d: 2 }.validate(&mut rng).assert_valid();
+    HyperCubeGraph { d: 3 }.validate(&mut rng).assert_valid();
+    HyperCubeGraph { d: 5 }.validate(&mut rng).assert_valid();
+    // a single vertex is a valid graph, though there is no edge to throw balls along
+    HyperCubeGraph::new(0)
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+    assert_eq!(HyperCubeGraph::new(64).err(), Some(Error::TooManyVertices));
+    assert_eq!(HyperCubeGraph::new(63).err(), Some(Error::TooManyVertices));
+    assert!(HyperCubeGraph::new(58).is_ok());
+    assert_eq!(HyperCubeGraph::new(59).err(), Some(Error::TooManyVertices));
 }
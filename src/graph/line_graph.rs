@@ -0,0 +1,214 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+
+use super::Graph;
+use crate::Error;
+
+// The line graph of a graph, with a vertex for each of its edges, two joined if the edges share
+// an endpoint
+// Vertex e is the e'th edge found going through the vertices of the graph in index order, see
+// 'edge' for its endpoints
+pub struct LineGraph {
+    // the endpoints of each edge by index, the smaller first
+    edges: Box<[(usize, usize)]>,
+    // the edges at vertex i of the graph are 'incident[offsets[i]..offsets[i + 1]]'
+    offsets: Box<[usize]>,
+    incident: Box<[usize]>,
+    // pairs[i] is the number of pairs of edges meeting at the vertices of the graph before i,
+    // each being an edge of the line graph
+    pairs: Box<[usize]>,
+    d: Option<usize>,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct LineVertex(usize);
+
+impl Display for LineVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for LineGraph {
+    type Vertex = LineVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> LineVertex>;
+    type NIter = std::vec::IntoIter<LineVertex>;
+
+    fn n(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn d(&self) -> Option<usize> {
+        self.d
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        let (a, b) = self.edges[v.0];
+        self.graph_degree(a) + self.graph_degree(b) - 2
+    }
+
+    fn m(&self) -> usize {
+        *self.pairs.last().unwrap()
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(LineVertex)
+    }
+
+    fn iter_neighbours(&self, v: LineVertex) -> Self::NIter {
+        // in a simple graph no other edge meets both endpoints of 'v'
+        let (a, b) = self.edges[v.0];
+        let res: Vec<_> = [a, b]
+            .iter()
+            .flat_map(|&end| &self.incident[self.offsets[end]..self.offsets[end + 1]])
+            .filter(|&&e| e != v.0)
+            .map(|&e| LineVertex(e))
+            .collect();
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        let ((a, b), (c, e)) = (self.edges[v.0], self.edges[u.0]);
+        v != u && (a == c || a == e || b == c || b == e)
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // each edge is a pair of edges of the graph meeting at exactly one vertex, so pick the
+        // vertex with probability proportional to its number of pairs and then a pair at it
+        let idx = rng.gen_range(0..self.m());
+        let end = self.pairs.partition_point(|&p| p <= idx) - 1;
+        let incident = &self.incident[self.offsets[end]..self.offsets[end + 1]];
+        let i = rng.gen_range(0..incident.len());
+        let mut j = rng.gen_range(0..incident.len() - 1);
+        if j >= i {
+            j += 1;
+        }
+        (LineVertex(incident[i]), LineVertex(incident[j]))
+    }
+}
+
+impl LineGraph {
+    pub fn new<G: Graph>(graph: &G) -> Result<Self, Error> {
+        let n = graph.n();
+        let mut edges = vec![];
+        for v in graph.iter_vertices() {
+            let v_idx = graph.as_idx(v);
+            for u in graph.iter_neighbours(v) {
+                let u_idx = graph.as_idx(u);
+                if v_idx < u_idx {
+                    edges.push((v_idx, u_idx));
+                }
+            }
+        }
+        if edges.is_empty() {
+            return Err(Error::NoVertices);
+        }
+        let mut offsets = vec![0; n + 1];
+        for &(a, b) in &edges {
+            offsets[a + 1] += 1;
+            offsets[b + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut fill = offsets.clone();
+        let mut incident = vec![0; 2 * edges.len()];
+        for (e, &(a, b)) in edges.iter().enumerate() {
+            for end in [a, b] {
+                incident[fill[end]] = e;
+                fill[end] += 1;
+            }
+        }
+        let mut pairs: Vec<usize> = Vec::with_capacity(n + 1);
+        pairs.push(0);
+        for i in 0..n {
+            let degree = offsets[i + 1] - offsets[i];
+            let total = degree
+                .checked_mul(degree.saturating_sub(1))
+                .and_then(|x| pairs[i].checked_add(x / 2));
+            pairs.push(total.ok_or(Error::TooManyVertices)?);
+        }
+        let degree =
+            |(a, b): (usize, usize)| offsets[a + 1] - offsets[a] + offsets[b + 1] - offsets[b] - 2;
+        let d = Some(degree(edges[0])).filter(|&d| edges.iter().all(|&e| degree(e) == d));
+        Ok(LineGraph {
+            edges: edges.into_boxed_slice(),
+            offsets: offsets.into_boxed_slice(),
+            incident: incident.into_boxed_slice(),
+            pairs: pairs.into_boxed_slice(),
+            d,
+        })
+    }
+
+    // The indexes in the graph of the endpoints of the edge 'v', the smaller first
+    pub fn edge(&self, v: LineVertex) -> (usize, usize) {
+        self.edges[v.0]
+    }
+
+    fn graph_degree(&self, end: usize) -> usize {
+        self.offsets[end + 1] - self.offsets[end]
+    }
+}
+
+#[cfg(test)]
+use super::{FullGraph, GridGraph, HyperCubeGraph, RingGraph, StarGraph};
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for n in 3..8 {
+        LineGraph::new(&RingGraph::new(n).unwrap())
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+        LineGraph::new(&StarGraph::new(n).unwrap())
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    LineGraph::new(&GridGraph::new(&[3, 4]).unwrap())
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+    LineGraph::new(&HyperCubeGraph::new(4).unwrap())
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+    assert_eq!(
+        LineGraph::new(&FullGraph::new(1).unwrap()).err(),
+        Some(Error::NoVertices)
+    );
+}
+
+#[test]
+fn test_structure() {
+    // the line graph of a ring is the same ring, that of a star is complete, and that of a
+    // d-regular graph is 2 (d - 1)-regular
+    let graph = LineGraph::new(&RingGraph::new(6).unwrap()).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (6, Some(2), 6));
+    let graph = LineGraph::new(&StarGraph::new(6).unwrap()).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (5, Some(4), 10));
+    let graph = LineGraph::new(&FullGraph::new(5).unwrap()).unwrap();
+    assert_eq!((graph.n(), graph.d()), (10, Some(6)));
+    // a path of three edges has a path of two as its line graph
+    let graph = LineGraph::new(&GridGraph::new(&[4]).unwrap()).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (3, None, 2));
+    let ends: Vec<_> = graph.iter_vertices().map(|v| graph.edge(v)).collect();
+    assert_eq!(ends, [(0, 1), (1, 2), (2, 3)]);
+    assert!(graph.has_edge(LineVertex(0), LineVertex(1)));
+    assert!(!graph.has_edge(LineVertex(0), LineVertex(2)));
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    // the grid's line graph has edges at vertices with two, three and four edges
+    let graph = LineGraph::new(&GridGraph::new(&[3, 3]).unwrap()).unwrap();
+    let mut rng = crate::rng::seeded(1);
+    super::validation::assert_random_edge_uniform(&graph, &mut rng);
+}
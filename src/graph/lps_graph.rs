@@ -0,0 +1,279 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::Graph;
+use crate::Error;
+
+// A 2 x 2 matrix over Z_q, [[a, b], [c, d]] stored as [a, b, c, d]
+type Matrix = [usize; 4];
+
+// The Lubotzky-Phillips-Sarnak Ramanujan graph X^{p,q} for distinct primes p, q = 1 mod 4, the
+// (p + 1)-regular Cayley graph of PSL(2, q) when p is a square mod q and of PGL(2, q) otherwise,
+// in which case it is bipartite
+// The generators come from the p + 1 ways of writing p = a0^2 + a1^2 + a2^2 + a3^2 with a0 > 0
+// odd and a1, a2, a3 even, a0 + a1 i + a2 j + a3 k being sent to the matrix
+// [[a0 + a1 x, a2 + a3 x], [-a2 + a3 x, a0 - a1 x]] with x^2 = -1 mod q
+// Matrices are taken up to scalars, so each vertex is represented by the one whose first
+// non-zero entry is 1, and indexed as described in 'index'
+pub struct LpsGraph {
+    p: usize,
+    q: usize,
+    // PSL(2, q) rather than PGL(2, q), so every determinant is a non-zero square
+    special: bool,
+    // the number of determinants a vertex can have, q - 1 or (q - 1) / 2 for PSL(2, q)
+    determinants: usize,
+    generators: Box<[Matrix]>,
+    // the indexes of the generators, sorted
+    generator_indexes: Box<[usize]>,
+    // inverses mod q, 0 having none
+    inverse: Box<[usize]>,
+    // the non-zero squares mod q in order, and rank[x] is the position of x among them if it is one
+    squares: Box<[usize]>,
+    rank: Box<[usize]>,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct LpsVertex(usize);
+
+impl Display for LpsVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for LpsGraph {
+    type Vertex = LpsVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> LpsVertex>;
+    type NIter = smallvec::IntoIter<[LpsVertex; 8]>;
+
+    fn n(&self) -> usize {
+        self.q * self.determinants * (self.q + 1)
+    }
+
+    fn d(&self) -> Option<usize> {
+        Some(self.p + 1)
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(LpsVertex)
+    }
+
+    fn iter_neighbours(&self, v: LpsVertex) -> Self::NIter {
+        let matrix = self.matrix(v.0);
+        self.generators
+            .iter()
+            .map(|&g| LpsVertex(self.index(self.mul(matrix, g))))
+            .collect::<SmallVec<_>>()
+            .into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        // u = v g for a generator g, the adjugate being a scalar multiple of the inverse
+        let [a, b, c, d] = self.matrix(v.0);
+        let q = self.q;
+        let adjugate = [d, (q - b) % q, (q - c) % q, a];
+        let g = self.index(self.mul(adjugate, self.matrix(u.0)));
+        self.generator_indexes.binary_search(&g).is_ok()
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        let v = LpsVertex(rng.gen_range(0..self.n()));
+        let g = self.generators[rng.gen_range(0..self.generators.len())];
+        (v, LpsVertex(self.index(self.mul(self.matrix(v.0), g))))
+    }
+}
+
+impl LpsGraph {
+    // Fails with 'LpsParameters' unless p and q are distinct primes, both 1 mod 4, with
+    // q > 2 sqrt(p), which keeps the generators distinct so the graph is simple
+    pub fn new(p: usize, q: usize) -> Result<Self, Error> {
+        // n is below q^3
+        if q.checked_mul(q).and_then(|q2| q2.checked_mul(q)).is_none() {
+            return Err(Error::TooManyVertices);
+        }
+        if p == q || !is_prime_one_mod_four(p) || !is_prime_one_mod_four(q) || q * q / 4 < p {
+            return Err(Error::LpsParameters { p, q });
+        }
+        let mut is_square = vec![false; q];
+        for x in 1..q {
+            is_square[x * x % q] = true;
+        }
+        let squares: Vec<usize> = (1..q).filter(|&x| is_square[x]).collect();
+        let mut rank = vec![0; q];
+        for (r, &x) in squares.iter().enumerate() {
+            rank[x] = r;
+        }
+        // q = (q / x) x + q % x, so x^-1 = -(q / x) (q % x)^-1
+        let mut inverse = vec![0, 1];
+        for x in 2..q {
+            inverse.push((q - q / x) * inverse[q % x] % q);
+        }
+        let i = (1..q).find(|&x| x * x % q == q - 1).unwrap();
+        let special = is_square[p % q];
+        let mut res = LpsGraph {
+            p,
+            q,
+            special,
+            determinants: if special { (q - 1) / 2 } else { q - 1 },
+            generators: Box::new([]),
+            generator_indexes: Box::new([]),
+            inverse: inverse.into_boxed_slice(),
+            squares: squares.into_boxed_slice(),
+            rank: rank.into_boxed_slice(),
+        };
+        let residue = |a: isize| a.rem_euclid(q as isize) as usize;
+        let root = (p as f64).sqrt() as isize + 1;
+        let mut generators = vec![];
+        for a0 in (1..=root).step_by(2) {
+            for a1 in (-root..=root).filter(|a| a % 2 == 0) {
+                for a2 in (-root..=root).filter(|a| a % 2 == 0) {
+                    for a3 in (-root..=root).filter(|a| a % 2 == 0) {
+                        if a0 * a0 + a1 * a1 + a2 * a2 + a3 * a3 != p as isize {
+                            continue;
+                        }
+                        let (a0, a1, a2, a3) = (residue(a0), residue(a1), residue(a2), residue(a3));
+                        generators.push([
+                            (a0 + a1 * i) % q,
+                            (a2 + a3 * i) % q,
+                            (q - a2 + a3 * i) % q,
+                            (a0 + (q - a1) * i) % q,
+                        ]);
+                    }
+                }
+            }
+        }
+        debug_assert_eq!(generators.len(), p + 1);
+        let mut generator_indexes: Vec<usize> = generators.iter().map(|&g| res.index(g)).collect();
+        generator_indexes.sort_unstable();
+        res.generators = generators.into_boxed_slice();
+        res.generator_indexes = generator_indexes.into_boxed_slice();
+        Ok(res)
+    }
+
+    // Whether the vertices are PSL(2, q), so the graph is not bipartite
+    pub fn is_special(&self) -> bool {
+        self.special
+    }
+
+    // The matrix representing 'v', whose first non-zero entry is 1
+    pub fn matrix_of(&self, v: LpsVertex) -> [usize; 4] {
+        self.matrix(v.0)
+    }
+
+    fn mul(&self, [a, b, c, d]: Matrix, [e, f, g, h]: Matrix) -> Matrix {
+        let q = self.q;
+        [
+            (a * e + b * g) % q,
+            (a * f + b * h) % q,
+            (c * e + d * g) % q,
+            (c * f + d * h) % q,
+        ]
+    }
+
+    // Matrices [[1, b], [c, d]] come first, ordered by b, c and then their determinant, followed
+    // by those of the form [[0, 1], [c, d]] ordered by their determinant -c and then d
+    // Determinants are numbered by 'rank_determinant'
+    fn index(&self, matrix: Matrix) -> usize {
+        let q = self.q;
+        let scale = if matrix[0] != 0 { matrix[0] } else { matrix[1] };
+        let [a, b, c, d] = matrix.map(|x| x * self.inverse[scale] % q);
+        if a == 1 {
+            let det = (d + q - b * c % q) % q;
+            (b * q + c) * self.determinants + self.rank_determinant(det)
+        } else {
+            q * q * self.determinants + self.rank_determinant((q - c) % q) * q + d
+        }
+    }
+
+    fn matrix(&self, idx: usize) -> Matrix {
+        let q = self.q;
+        let first = q * q * self.determinants;
+        if idx < first {
+            let (bc, det) = (idx / self.determinants, idx % self.determinants);
+            let (b, c) = (bc / q, bc % q);
+            [1, b, c, (self.unrank_determinant(det) + b * c) % q]
+        } else {
+            let (det, d) = ((idx - first) / q, (idx - first) % q);
+            [0, 1, q - self.unrank_determinant(det), d]
+        }
+    }
+
+    // The position of a determinant among those the vertices can have
+    fn rank_determinant(&self, det: usize) -> usize {
+        match self.special {
+            true => self.rank[det],
+            false => det - 1,
+        }
+    }
+
+    fn unrank_determinant(&self, rank: usize) -> usize {
+        match self.special {
+            true => self.squares[rank],
+            false => rank + 1,
+        }
+    }
+}
+
+fn is_prime_one_mod_four(p: usize) -> bool {
+    p % 4 == 1
+        && p > 1
+        && (2..)
+            .take_while(|x| x * x <= p)
+            .all(|x| !p.is_multiple_of(x))
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for (p, q) in [(5, 13), (5, 17), (13, 17), (17, 13), (5, 29), (29, 13)] {
+        LpsGraph::new(p, q)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    // 13 > 2 sqrt(29) but 5 < 2 sqrt(13)
+    for (p, q) in [(5, 5), (7, 13), (5, 3), (9, 13), (5, 21), (13, 5), (13, 1)] {
+        assert_eq!(
+            LpsGraph::new(p, q).err(),
+            Some(Error::LpsParameters { p, q })
+        );
+    }
+}
+
+#[test]
+fn test_structure() {
+    // 5 is a square mod 29 but not mod 13 or 17
+    for (p, q, special) in [
+        (5, 13, false),
+        (5, 17, false),
+        (5, 29, true),
+        (13, 17, true),
+    ] {
+        let graph = LpsGraph::new(p, q).unwrap();
+        assert_eq!(graph.is_special(), special);
+        let order = q * (q * q - 1);
+        assert_eq!(graph.n(), if special { order / 2 } else { order });
+        assert_eq!(graph.d(), Some(p + 1));
+        // the generators have determinant p, so when p is not a square they join matrices whose
+        // determinant is a square to ones whose determinant is not
+        let square = |v| {
+            let [a, b, c, d] = graph.matrix_of(v);
+            let det = (a * d + q * q - b * c) % q;
+            graph.squares.binary_search(&det).is_ok()
+        };
+        for v in graph.iter_vertices() {
+            if special {
+                assert!(square(v));
+            } else {
+                assert!(graph.iter_neighbours(v).all(|u| square(u) != square(v)));
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::Graph;
+use crate::Error;
+
+// The Margulis-Gabber-Galil expander on Z_m x Z_m, joining (x, y) to (x +- 2y, y),
+// (x +- (2y + 1), y), (x, y +- 2x) and (x, y +- (2x + 1))
+// This is an 8-regular multigraph, and since 'Graph' is simple its loops and repeated edges are
+// dropped, so the vertices such as (0, 0) where the maps coincide, a fraction of about 6 / m,
+// have lower degree
+// Each vertex (x, y) has index x * m + y
+pub struct MargulisGraph {
+    m: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct MargulisVertex(usize);
+
+impl Display for MargulisVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Graph for MargulisGraph {
+    type Vertex = MargulisVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> MargulisVertex>;
+    type NIter = smallvec::IntoIter<[MargulisVertex; 8]>;
+
+    fn n(&self) -> usize {
+        self.m * self.m
+    }
+
+    fn d(&self) -> Option<usize> {
+        None
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        self.iter_neighbours(v).len()
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(MargulisVertex)
+    }
+
+    fn iter_neighbours(&self, v: MargulisVertex) -> Self::NIter {
+        let mut res = SmallVec::new();
+        for u in self.images(v) {
+            if u != v && !res.contains(&u) {
+                res.push(u);
+            }
+        }
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        // the maps are closed under inverses, so this is symmetric
+        u != v && self.images(v).contains(&u)
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // a random map applied to a random vertex picks each edge of the multigraph uniformly, so
+        // rejecting loops and keeping an edge with multiplicity c with probability 1 / c leaves
+        // each edge of the simple graph equally likely
+        loop {
+            let v = MargulisVertex(rng.gen_range(0..self.n()));
+            let images = self.images(v);
+            let u = images[rng.gen_range(0..images.len())];
+            let c = images.iter().filter(|&&w| w == u).count();
+            if u != v && rng.gen_range(0..c) == 0 {
+                return (v, u);
+            }
+        }
+    }
+}
+
+impl MargulisGraph {
+    pub fn new(m: usize) -> Result<Self, Error> {
+        match m {
+            0 => Err(Error::NoVertices),
+            // every map fixes the single vertex, so there are only loops
+            1 => Err(Error::NoEdges),
+            _ if m.checked_mul(m).is_none() => Err(Error::TooManyVertices),
+            _ => Ok(MargulisGraph { m }),
+        }
+    }
+
+    pub fn vertex(&self, x: usize, y: usize) -> MargulisVertex {
+        MargulisVertex(x % self.m * self.m + y % self.m)
+    }
+
+    pub fn coordinates(&self, v: MargulisVertex) -> (usize, usize) {
+        (v.0 / self.m, v.0 % self.m)
+    }
+
+    // The images of 'v' under the eight maps, including loops and repeats
+    fn images(&self, v: MargulisVertex) -> [MargulisVertex; 8] {
+        let m = self.m;
+        let (x, y) = self.coordinates(v);
+        // m * m fits in a usize, so 2 * m does too
+        let add = |a: usize, b: usize| (a + b % m) % m;
+        let sub = |a: usize, b: usize| (a + m - b % m) % m;
+        let (y2, x2) = (2 * y, 2 * x);
+        [
+            self.vertex(add(x, y2), y),
+            self.vertex(sub(x, y2), y),
+            self.vertex(add(x, y2 + 1), y),
+            self.vertex(sub(x, y2 + 1), y),
+            self.vertex(x, add(y, x2)),
+            self.vertex(x, sub(y, x2)),
+            self.vertex(x, add(y, x2 + 1)),
+            self.vertex(x, sub(y, x2 + 1)),
+        ]
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for m in [2, 3, 4, 7, 10, 16] {
+        MargulisGraph::new(m)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(MargulisGraph::new(0).err(), Some(Error::NoVertices));
+    assert_eq!(MargulisGraph::new(1).err(), Some(Error::NoEdges));
+    assert_eq!(
+        MargulisGraph::new(usize::MAX).err(),
+        Some(Error::TooManyVertices)
+    );
+}
+
+#[test]
+fn test_structure() {
+    let graph = MargulisGraph::new(10).unwrap();
+    let mut neighbours: Vec<_> = graph
+        .iter_neighbours(graph.vertex(3, 4))
+        .map(|u| graph.coordinates(u))
+        .collect();
+    neighbours.sort();
+    assert_eq!(
+        neighbours,
+        [
+            (1, 4),
+            (2, 4),
+            (3, 0),
+            (3, 1),
+            (3, 7),
+            (3, 8),
+            (4, 4),
+            (5, 4)
+        ]
+    );
+    // (0, 0) is fixed by the maps without the + 1, leaving four neighbours
+    assert_eq!(graph.degree(graph.vertex(0, 0)), 4);
+    // but most vertices have all eight
+    let graph = MargulisGraph::new(101).unwrap();
+    let full = graph.iter_vertices().filter(|&v| graph.degree(v) == 8);
+    assert_eq!(full.count(), 9604);
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    // m = 4 has repeated edges and loops, which must not make their edges more or less likely
+    let graph = MargulisGraph::new(4).unwrap();
+    let mut rng = crate::rng::seeded(1);
+    super::validation::assert_random_edge_uniform(&graph, &mut rng);
+}
@@ -1,12 +1,63 @@
 pub use self::{
-    arbitrary_graph::ArbitraryGraph, full_graph::FullGraph, graph::*,
-    hyper_cube_graph::HyperCubeGraph, ring_graph::RingGraph, torus_graph::TorusGraph,
+    arbitrary_graph::ArbitraryGraph,
+    bin_storage::{BinStorage, NarrowBins, RelativeBins, WideBins},
+    butterfly_graph::{ButterflyGraph, ButterflyVertex},
+    cayley_graph::{CayleyGraph, CayleyVertex},
+    complement_graph::ComplementGraph,
+    csr_graph::{CsrGraph, CsrNeighbours, CsrVertex},
+    cube_connected_cycles_graph::{CubeConnectedCyclesGraph, CubeConnectedCyclesVertex},
+    de_bruijn_graph::{DeBruijnGraph, DeBruijnVertex},
+    disjoint_union_graph::{DisjointUnionGraph, UnionVertex},
+    fat_tree_graph::{FatTreeGraph, FatTreeNode, FatTreeVertex},
+    full_graph::FullGraph,
+    geometric_graph::GeometricGraph,
+    graph::*,
+    graph6::{graph6_code, parse_graph6, to_graph6, to_sparse6},
+    grid_graph::{GridGraph, GridVertex},
+    hamming_graph::{HammingGraph, HammingVertex},
+    hyper_cube_graph::HyperCubeGraph,
+    line_graph::{LineGraph, LineVertex},
+    lps_graph::{LpsGraph, LpsVertex},
+    margulis_graph::{MargulisGraph, MargulisVertex},
+    product_graph::{ProductGraph, ProductKind, ProductVertex},
+    random_models::{BarabasiAlbert, ChungLu, Gnm, Gnp, RandomGraphModel},
+    regular::{
+        check_regular, ConfigurationModel, McKayWormald, RegularGenerator, StegerWormald,
+        SwitchChain,
+    },
+    ring_graph::RingGraph,
+    star_graph::StarGraph,
+    torus_graph::TorusGraph,
+    validation::{ValidationReport, Violation},
 };
 
 mod arbitrary_graph;
+mod bin_storage;
+mod butterfly_graph;
+mod cayley_graph;
+mod complement_graph;
+mod csr_graph;
+mod cube_connected_cycles_graph;
+mod de_bruijn_graph;
 mod deg;
+mod disjoint_union_graph;
+mod fat_tree_graph;
 mod full_graph;
+mod geometric_graph;
 mod graph;
+mod graph6;
+mod grid_graph;
+mod hamming_graph;
 mod hyper_cube_graph;
+mod line_graph;
+mod lps_graph;
+mod margulis_graph;
+mod product_graph;
+mod random_models;
+mod regular;
 mod ring_graph;
+mod star_graph;
 mod torus_graph;
+#[cfg(test)]
+mod uniformity;
+mod validation;
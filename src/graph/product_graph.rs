@@ -0,0 +1,297 @@
+use std::fmt::{Display, Formatter};
+
+use rand::Rng;
+
+use super::{Bounded, Graph};
+use crate::Error;
+
+// Which pairs of vertices a 'ProductGraph' joins
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProductKind {
+    // (v, u) -- (v', u') if v = v' and u -- u', or u = u' and v -- v', so the torus is the
+    // Cartesian product of rings and the grid that of paths
+    Cartesian,
+    // (v, u) -- (v', u') if v -- v' and u -- u'
+    Tensor,
+    // the edges of both of the above, so the strong product of complete graphs is complete
+    Strong,
+}
+
+// The Cartesian, tensor or strong product of two graphs, on the pairs of their vertices
+// Vertex (v, u) has index 'g.as_idx(v) * h.n() + h.as_idx(u)', and is on the boundary if either
+// v or u is
+pub struct ProductGraph<G: Graph, H: Graph> {
+    g: G,
+    h: H,
+    kind: ProductKind,
+    // the vertices of each factor, so a random one can be picked
+    g_vertices: Box<[G::Vertex]>,
+    h_vertices: Box<[H::Vertex]>,
+    g_m: usize,
+    h_m: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct ProductVertex<V, U>(pub V, pub U);
+
+// Written like the sides of a torus, eg. 3x4, so it is a single word in every output format
+impl<V: Display, U: Display> Display for ProductVertex<V, U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.0, self.1)
+    }
+}
+
+impl<G: Graph, H: Graph> Graph for ProductGraph<G, H> {
+    type Vertex = ProductVertex<G::Vertex, H::Vertex>;
+    type VIter = std::vec::IntoIter<Self::Vertex>;
+    type NIter = std::vec::IntoIter<Self::Vertex>;
+
+    fn n(&self) -> usize {
+        self.g_vertices.len() * self.h_vertices.len()
+    }
+
+    fn d(&self) -> Option<usize> {
+        let (g_d, h_d) = (self.g.d()?, self.h.d()?);
+        Some(match self.kind {
+            ProductKind::Cartesian => g_d + h_d,
+            ProductKind::Tensor => g_d * h_d,
+            ProductKind::Strong => g_d + h_d + g_d * h_d,
+        })
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        let (g_d, h_d) = (self.g.degree(v.0), self.h.degree(v.1));
+        match self.kind {
+            ProductKind::Cartesian => g_d + h_d,
+            ProductKind::Tensor => g_d * h_d,
+            ProductKind::Strong => g_d + h_d + g_d * h_d,
+        }
+    }
+
+    fn m(&self) -> usize {
+        let [g_edges, h_edges, tensor_edges] = self.edge_counts();
+        g_edges + h_edges + tensor_edges
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        self.g.as_idx(v.0) * self.h_vertices.len() + self.h.as_idx(v.1)
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        let res: Vec<_> = self
+            .g_vertices
+            .iter()
+            .flat_map(|&v| self.h_vertices.iter().map(move |&u| ProductVertex(v, u)))
+            .collect();
+        res.into_iter()
+    }
+
+    fn iter_neighbours(&self, v: Self::Vertex) -> Self::NIter {
+        let ProductVertex(g_v, h_v) = v;
+        let mut res = vec![];
+        if self.kind != ProductKind::Tensor {
+            res.extend(
+                self.g
+                    .iter_neighbours(g_v)
+                    .map(|g_u| ProductVertex(g_u, h_v)),
+            );
+            res.extend(
+                self.h
+                    .iter_neighbours(h_v)
+                    .map(|h_u| ProductVertex(g_v, h_u)),
+            );
+        }
+        if self.kind != ProductKind::Cartesian {
+            for g_u in self.g.iter_neighbours(g_v) {
+                res.extend(
+                    self.h
+                        .iter_neighbours(h_v)
+                        .map(|h_u| ProductVertex(g_u, h_u)),
+                );
+            }
+        }
+        res.into_iter()
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        let (g_equal, h_equal) = (v.0 == u.0, v.1 == u.1);
+        let (g_edge, h_edge) = (self.g.has_edge(v.0, u.0), self.h.has_edge(v.1, u.1));
+        match self.kind {
+            ProductKind::Cartesian => (g_equal && h_edge) || (h_equal && g_edge),
+            ProductKind::Tensor => g_edge && h_edge,
+            ProductKind::Strong => {
+                (g_equal || g_edge) && (h_equal || h_edge) && !(g_equal && h_equal)
+            }
+        }
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // pick the kind of edge with probability proportional to how many there are, each kind
+        // being uniform on its own
+        let [g_edges, h_edges, _] = self.edge_counts();
+        let idx = rng.gen_range(0..self.m());
+        if idx < g_edges {
+            let (g_v, g_u) = self.g.random_edge(rng);
+            let h_v = self.h_vertices[rng.gen_range(0..self.h_vertices.len())];
+            (ProductVertex(g_v, h_v), ProductVertex(g_u, h_v))
+        } else if idx < g_edges + h_edges {
+            let g_v = self.g_vertices[rng.gen_range(0..self.g_vertices.len())];
+            let (h_v, h_u) = self.h.random_edge(rng);
+            (ProductVertex(g_v, h_v), ProductVertex(g_v, h_u))
+        } else {
+            // each edge comes from two of the four ways to orient a pair of edges, one the
+            // reverse of the other
+            let ((g_v, g_u), (h_v, h_u)) = (self.g.random_edge(rng), self.h.random_edge(rng));
+            (ProductVertex(g_v, h_v), ProductVertex(g_u, h_u))
+        }
+    }
+}
+
+impl<G: Bounded, H: Bounded> Bounded for ProductGraph<G, H> {
+    fn is_boundary(&self, v: Self::Vertex) -> bool {
+        self.g.is_boundary(v.0) || self.h.is_boundary(v.1)
+    }
+}
+
+impl<G: Graph, H: Graph> ProductGraph<G, H> {
+    pub fn new(g: G, h: H, kind: ProductKind) -> Result<Self, Error> {
+        let (g_vertices, h_vertices): (Box<[_]>, Box<[_]>) =
+            (g.iter_vertices().collect(), h.iter_vertices().collect());
+        let (g_n, h_n, g_m, h_m) = (g_vertices.len(), h_vertices.len(), g.m(), h.m());
+        // the edges of every kind are counted when picking a random one, so must fit too
+        let edges = || {
+            g_n.checked_mul(h_n)?;
+            let g_edges = g_m.checked_mul(h_n)?;
+            let h_edges = h_m.checked_mul(g_n)?;
+            let tensor_edges = g_m.checked_mul(h_m)?.checked_mul(2)?;
+            g_edges.checked_add(h_edges)?.checked_add(tensor_edges)
+        };
+        edges().ok_or(Error::TooManyVertices)?;
+        Ok(ProductGraph {
+            g,
+            h,
+            kind,
+            g_vertices,
+            h_vertices,
+            g_m,
+            h_m,
+        })
+    }
+
+    pub fn cartesian(g: G, h: H) -> Result<Self, Error> {
+        Self::new(g, h, ProductKind::Cartesian)
+    }
+
+    pub fn tensor(g: G, h: H) -> Result<Self, Error> {
+        Self::new(g, h, ProductKind::Tensor)
+    }
+
+    pub fn strong(g: G, h: H) -> Result<Self, Error> {
+        Self::new(g, h, ProductKind::Strong)
+    }
+
+    pub fn kind(&self) -> ProductKind {
+        self.kind
+    }
+
+    pub fn factors(&self) -> (&G, &H) {
+        (&self.g, &self.h)
+    }
+
+    // The number of edges along G with H fixed, along H with G fixed, and along both
+    fn edge_counts(&self) -> [usize; 3] {
+        let (g_n, h_n) = (self.g_vertices.len(), self.h_vertices.len());
+        let (g_edges, h_edges) = (self.g_m * h_n, self.h_m * g_n);
+        match self.kind {
+            ProductKind::Cartesian => [g_edges, h_edges, 0],
+            ProductKind::Tensor => [0, 0, 2 * self.g_m * self.h_m],
+            ProductKind::Strong => [g_edges, h_edges, 2 * self.g_m * self.h_m],
+        }
+    }
+}
+
+#[cfg(test)]
+use super::{FullGraph, GridGraph, RingGraph, StarGraph, TorusGraph};
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for kind in [
+        ProductKind::Cartesian,
+        ProductKind::Tensor,
+        ProductKind::Strong,
+    ] {
+        let graph = ProductGraph::new(RingGraph::new(5).unwrap(), StarGraph::new(4).unwrap(), kind);
+        graph.unwrap().validate(&mut rng).assert_valid();
+        let graph = ProductGraph::new(FullGraph::new(3).unwrap(), FullGraph::new(1).unwrap(), kind);
+        graph.unwrap().validate(&mut rng).assert_valid();
+        let ring = RingGraph::new(3).unwrap();
+        let nested = ProductGraph::new(ring, StarGraph::new(3).unwrap(), kind).unwrap();
+        let graph = ProductGraph::new(nested, GridGraph::new(&[2, 3]).unwrap(), kind);
+        graph.unwrap().validate(&mut rng).assert_valid();
+    }
+}
+
+#[test]
+fn test_structure() {
+    // the Cartesian product of rings is the torus, with the same indexes
+    let torus = TorusGraph::new(&[4, 5]).unwrap();
+    let product = ProductGraph::cartesian(RingGraph::new(4).unwrap(), RingGraph::new(5).unwrap());
+    let product = product.unwrap();
+    assert_eq!((product.n(), product.d(), product.m()), (20, Some(4), 40));
+    for v in product.iter_vertices() {
+        let mut neighbours: Vec<usize> = product
+            .iter_neighbours(v)
+            .map(|u| product.as_idx(u))
+            .collect();
+        neighbours.sort();
+        let torus_v = torus.iter_vertices().nth(product.as_idx(v)).unwrap();
+        let mut expected: Vec<usize> = torus
+            .iter_neighbours(torus_v)
+            .map(|u| torus.as_idx(u))
+            .collect();
+        expected.sort();
+        assert_eq!(neighbours, expected);
+    }
+    // the strong product of complete graphs is complete
+    let strong = ProductGraph::strong(FullGraph::new(3).unwrap(), FullGraph::new(4).unwrap());
+    assert_eq!(strong.unwrap().d(), Some(11));
+    // a ring of even length is bipartite, so its tensor product with an edge is two rings
+    let tensor = ProductGraph::tensor(RingGraph::new(6).unwrap(), FullGraph::new(2).unwrap());
+    let tensor = tensor.unwrap();
+    assert_eq!((tensor.d(), tensor.m()), (Some(2), 12));
+    let v = ProductVertex(tensor.g_vertices[0], tensor.h_vertices[0]);
+    let reached: Vec<usize> = tensor
+        .iter_vertices()
+        .filter(|&u| tensor.has_edge(v, u))
+        .map(|u| tensor.as_idx(u))
+        .collect();
+    assert_eq!(reached, [3, 11]);
+    assert_eq!(v.to_string(), "0x0");
+    let dot = super::BinGraph::<_>::new(tensor).unwrap().to_string();
+    assert!(dot.contains("\"0x0\" -- \"1x1\""), "{dot}");
+    // the product of grids has the boundary of either factor
+    let grid =
+        ProductGraph::cartesian(GridGraph::new(&[3]).unwrap(), GridGraph::new(&[3]).unwrap());
+    let grid = grid.unwrap();
+    let interior: Vec<usize> = grid
+        .iter_vertices()
+        .filter(|&v| !grid.is_boundary(v))
+        .map(|v| grid.as_idx(v))
+        .collect();
+    assert_eq!(interior, [4]);
+}
+
+#[test]
+fn test_random_edge_uniform() {
+    let mut rng = crate::rng::seeded(1);
+    for kind in [
+        ProductKind::Cartesian,
+        ProductKind::Tensor,
+        ProductKind::Strong,
+    ] {
+        let graph = ProductGraph::new(StarGraph::new(3).unwrap(), RingGraph::new(3).unwrap(), kind)
+            .unwrap();
+        super::validation::assert_random_edge_uniform(&graph, &mut rng);
+    }
+}
@@ -0,0 +1,341 @@
+use rand::seq::index;
+use rand::Rng;
+use smallvec::SmallVec;
+
+use super::CsrGraph;
+use crate::Error;
+
+// A model of random simple graphs whose degrees need not be equal, unlike 'RegularGenerator'
+// Random graphs are rarely connected, see 'CsrGraph::giant_component'
+pub trait RandomGraphModel {
+    // Fails if the parameters describe no graph, though a graph may still come out without edges
+    fn check(&self) -> Result<(), Error>;
+
+    // Fails if 'check' does
+    fn generate(&self, rng: &mut impl Rng) -> Result<CsrGraph, Error>;
+}
+
+// The Erdős–Rényi graph G(n, p), each pair of vertices being joined independently with
+// probability p
+pub struct Gnp {
+    pub n: usize,
+    pub p: f64,
+}
+
+// The Erdős–Rényi graph G(n, m), uniform over the graphs on n vertices with m edges
+pub struct Gnm {
+    pub n: usize,
+    pub m: usize,
+}
+
+// The Chung–Lu graph, joining u and v independently with probability
+// min(1, weights[u] * weights[v] / sum(weights)), so vertex v has expected degree about
+// weights[v] as long as no weight is much more than the square root of the sum
+pub struct ChungLu {
+    pub weights: Vec<f64>,
+}
+
+// Barabási–Albert preferential attachment, starting from the complete graph on m + 1 vertices and
+// joining each vertex added after that to m distinct earlier vertices, each chosen with
+// probability proportional to its degree
+pub struct BarabasiAlbert {
+    pub n: usize,
+    pub m: usize,
+}
+
+impl RandomGraphModel for Gnp {
+    fn check(&self) -> Result<(), Error> {
+        if self.n == 0 {
+            Err(Error::NoVertices)
+        } else if !(0.0..=1.0).contains(&self.p) {
+            Err(Error::InvalidProbability)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn generate(&self, rng: &mut impl Rng) -> Result<CsrGraph, Error> {
+        self.check()?;
+        let (n, p) = (self.n, self.p);
+        // Batagelj and Brandes' method, skipping straight to the next pair joined rather than
+        // visiting every pair, where the pairs (v, w) with w < v are in order of v and then w
+        let mut edges = vec![];
+        if p == 1.0 {
+            edges.extend((0..n).flat_map(|v| (0..v).map(move |w| (v, w))));
+        } else if p > 0.0 {
+            let (mut v, mut w) = (1, 0usize);
+            loop {
+                w = w.saturating_add(skip(rng, p));
+                while w >= v && v < n {
+                    w -= v;
+                    v += 1;
+                }
+                if v >= n {
+                    break;
+                }
+                edges.push((v, w));
+                w += 1;
+            }
+        }
+        CsrGraph::from_edges(n, &edges)
+    }
+}
+
+impl Gnm {
+    // The number of pairs of vertices
+    fn pairs(&self) -> Result<usize, Error> {
+        if self.n == 0 {
+            return Err(Error::NoVertices);
+        }
+        let twice = self.n.checked_mul(self.n - 1);
+        Ok(twice.ok_or(Error::TooManyVertices)? / 2)
+    }
+}
+
+impl RandomGraphModel for Gnm {
+    fn check(&self) -> Result<(), Error> {
+        let (n, m) = (self.n, self.m);
+        if m > self.pairs()? {
+            Err(Error::TooManyEdges { n, m })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn generate(&self, rng: &mut impl Rng) -> Result<CsrGraph, Error> {
+        self.check()?;
+        let (n, m, pairs) = (self.n, self.m, self.pairs()?);
+        // pair k is (v, k - v (v - 1) / 2) for the largest v with v (v - 1) / 2 <= k
+        let edges: Vec<(usize, usize)> = index::sample(rng, pairs, m)
+            .into_iter()
+            .map(|k| {
+                let mut v = ((1.0 + (1.0 + 8.0 * k as f64).sqrt()) / 2.0) as usize;
+                while v * (v - 1) / 2 > k {
+                    v -= 1;
+                }
+                while (v + 1) * v / 2 <= k {
+                    v += 1;
+                }
+                (v, k - v * (v - 1) / 2)
+            })
+            .collect();
+        CsrGraph::from_edges(n, &edges)
+    }
+}
+
+impl ChungLu {
+    // Weights following a power law with the given exponent, the i'th largest being proportional
+    // to i^(-1 / (exponent - 1)), scaled so they average 'average_degree'
+    // Exponents between 2 and 3 give the heavy tails seen in real networks
+    pub fn power_law(n: usize, exponent: f64, average_degree: f64) -> Result<Self, Error> {
+        if exponent.is_nan() || exponent <= 1.0 {
+            return Err(Error::InvalidExponent);
+        }
+        let weights: Vec<f64> = (1..=n)
+            .map(|i| (i as f64).powf(-1.0 / (exponent - 1.0)))
+            .collect();
+        let scale = average_degree * n as f64 / weights.iter().sum::<f64>();
+        Ok(ChungLu {
+            weights: weights.iter().map(|w| w * scale).collect(),
+        })
+    }
+}
+
+impl RandomGraphModel for ChungLu {
+    fn check(&self) -> Result<(), Error> {
+        if self.weights.is_empty() {
+            Err(Error::NoVertices)
+        } else if !self.weights.iter().all(|w| w.is_finite() && *w >= 0.0) {
+            Err(Error::InvalidWeights)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn generate(&self, rng: &mut impl Rng) -> Result<CsrGraph, Error> {
+        self.check()?;
+        let n = self.weights.len();
+        let total: f64 = self.weights.iter().sum();
+        // Miller and Hagberg's method, going through the vertices in decreasing order of weight
+        // so the probabilities for each vertex decrease, skipping ahead as if they were all the
+        // current one and then accepting the pair reached with the ratio of its true probability
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&u, &v| self.weights[v].total_cmp(&self.weights[u]));
+        let probability = |u: usize, v: usize| {
+            if total == 0.0 {
+                0.0
+            } else {
+                (self.weights[u] * self.weights[v] / total).min(1.0)
+            }
+        };
+        let mut edges = vec![];
+        for (i, &u) in order.iter().enumerate() {
+            let mut j = i + 1;
+            let mut p = order.get(j).map_or(0.0, |&v| probability(u, v));
+            while j < n && p > 0.0 {
+                if p < 1.0 {
+                    j = j.saturating_add(skip(rng, p));
+                }
+                if j < n {
+                    let q = probability(u, order[j]);
+                    if rng.gen::<f64>() < q / p {
+                        edges.push((u, order[j]));
+                    }
+                    p = q;
+                    j += 1;
+                }
+            }
+        }
+        CsrGraph::from_edges(n, &edges)
+    }
+}
+
+impl RandomGraphModel for BarabasiAlbert {
+    fn check(&self) -> Result<(), Error> {
+        let (n, m) = (self.n, self.m);
+        if n == 0 {
+            Err(Error::NoVertices)
+        } else if m == 0 {
+            Err(Error::NoEdges)
+        } else if m >= n {
+            Err(Error::DegreeTooLarge { n, d: m })
+        } else if n.checked_mul(m).and_then(|nm| nm.checked_mul(2)).is_none() {
+            Err(Error::TooManyVertices)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn generate(&self, rng: &mut impl Rng) -> Result<CsrGraph, Error> {
+        self.check()?;
+        let (n, m) = (self.n, self.m);
+        let mut edges: Vec<(usize, usize)> =
+            (0..=m).flat_map(|v| (0..v).map(move |w| (v, w))).collect();
+        // each vertex appears once for each of its edges, so a random entry is a vertex chosen
+        // with probability proportional to its degree
+        let mut endpoints: Vec<usize> = edges.iter().flat_map(|&(v, w)| [v, w]).collect();
+        for v in m + 1..n {
+            let mut targets: SmallVec<[usize; 8]> = SmallVec::new();
+            while targets.len() < m {
+                let w = endpoints[rng.gen_range(0..endpoints.len())];
+                if !targets.contains(&w) {
+                    targets.push(w);
+                }
+            }
+            for w in targets {
+                edges.push((v, w));
+                endpoints.extend([v, w]);
+            }
+        }
+        CsrGraph::from_edges(n, &edges)
+    }
+}
+
+// The number of failures before the next success in independent trials with success
+// probability 0 < p < 1
+fn skip(rng: &mut impl Rng, p: f64) -> usize {
+    // 1 - gen is in (0, 1], so its logarithm is finite, and the cast saturates
+    let r: f64 = 1.0 - rng.gen::<f64>();
+    (r.ln() / (1.0 - p).ln()).floor() as usize
+}
+
+#[cfg(test)]
+fn assert_close(x: f64, expected: f64, tolerance: f64) {
+    assert!((x - expected).abs() <= tolerance, "{x} vs {expected}");
+}
+
+#[test]
+fn test_valid() {
+    use super::Graph;
+
+    let mut rng = crate::rng::seeded(0);
+    for p in [0.0, 0.01, 0.1, 0.5, 1.0] {
+        let graph = Gnp { n: 50, p }.generate(&mut rng).unwrap();
+        graph.validate(&mut rng).assert_valid();
+    }
+    for m in [0, 1, 100, 1225] {
+        let graph = Gnm { n: 50, m }.generate(&mut rng).unwrap();
+        assert_eq!(graph.m(), m);
+        graph.validate(&mut rng).assert_valid();
+    }
+    for (exponent, average) in [(2.1, 3.0), (2.5, 10.0), (3.0, 1.0)] {
+        let graph = ChungLu::power_law(50, exponent, average)
+            .unwrap()
+            .generate(&mut rng)
+            .unwrap();
+        graph.validate(&mut rng).assert_valid();
+    }
+    for m in [1, 2, 5] {
+        let graph = BarabasiAlbert { n: 50, m }.generate(&mut rng).unwrap();
+        assert_eq!(graph.m(), m * (m + 1) / 2 + (50 - m - 1) * m);
+        assert!(graph.iter_vertices().all(|v| graph.degree(v) >= m));
+        graph.validate(&mut rng).assert_valid();
+    }
+}
+
+#[test]
+fn test_invalid() {
+    let mut rng = crate::rng::seeded(0);
+    assert_eq!(
+        Gnp { n: 0, p: 0.5 }.generate(&mut rng).err(),
+        Some(Error::NoVertices)
+    );
+    assert_eq!(
+        Gnp { n: 5, p: 1.5 }.generate(&mut rng).err(),
+        Some(Error::InvalidProbability)
+    );
+    assert_eq!(
+        Gnm { n: 5, m: 11 }.generate(&mut rng).err(),
+        Some(Error::TooManyEdges { n: 5, m: 11 })
+    );
+    assert_eq!(
+        ChungLu {
+            weights: vec![1.0, -1.0]
+        }
+        .generate(&mut rng)
+        .err(),
+        Some(Error::InvalidWeights)
+    );
+    assert_eq!(
+        ChungLu::power_law(10, 1.0, 2.0).err(),
+        Some(Error::InvalidExponent)
+    );
+    assert_eq!(
+        BarabasiAlbert { n: 3, m: 3 }.generate(&mut rng).err(),
+        Some(Error::DegreeTooLarge { n: 3, d: 3 })
+    );
+}
+
+#[test]
+fn test_degrees() {
+    use super::Graph;
+
+    let mut rng = crate::rng::seeded(1);
+    // G(n, p) has p n (n - 1) / 2 edges on average, with standard deviation about 22 here
+    let mut total = 0;
+    for _ in 0..20 {
+        total += Gnp { n: 1000, p: 0.001 }.generate(&mut rng).unwrap().m();
+    }
+    assert_close(total as f64 / 20.0, 499.5, 15.0);
+    // each vertex of a Chung–Lu graph has expected degree its weight
+    let weights: Vec<f64> = (0..200).map(|v| if v < 100 { 2.0 } else { 8.0 }).collect();
+    let mut degrees = [0; 2];
+    for _ in 0..20 {
+        let graph = ChungLu {
+            weights: weights.clone(),
+        }
+        .generate(&mut rng)
+        .unwrap();
+        for v in graph.iter_vertices() {
+            degrees[graph.as_idx(v) / 100] += graph.degree(v);
+        }
+    }
+    assert_close(degrees[0] as f64 / 2000.0, 2.0, 0.1);
+    assert_close(degrees[1] as f64 / 2000.0, 8.0, 0.3);
+    // preferential attachment favours the oldest vertices
+    let graph = BarabasiAlbert { n: 2000, m: 2 }.generate(&mut rng).unwrap();
+    let degrees: Vec<usize> = graph.iter_vertices().map(|v| graph.degree(v)).collect();
+    let oldest: usize = degrees[..100].iter().sum();
+    let newest: usize = degrees[1900..].iter().sum();
+    assert!(oldest > 4 * newest);
+}
@@ -0,0 +1,219 @@
+use itertools::Itertools;
+use rand::Rng;
+
+use super::{deg, ArbitraryGraph, Graph};
+use crate::Error;
+
+// A way of generating random simple d-regular graphs on n vertices
+// Fails if there is no such graph, or it has no edges
+pub trait RegularGenerator {
+    fn generate(&self, n: usize, d: usize, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error>;
+}
+
+// Checks that simple d-regular graphs on n vertices exist and have edges, which is exactly when
+// the generators terminate
+pub fn check_regular(n: usize, d: usize) -> Result<(), Error> {
+    if n == 0 {
+        Err(Error::NoVertices)
+    } else if d == 0 {
+        Err(Error::NoEdges)
+    } else if d >= n {
+        Err(Error::DegreeTooLarge { n, d })
+    } else if n.checked_mul(d).is_none() {
+        Err(Error::TooManyVertices)
+    } else if n * d % 2 == 1 {
+        Err(Error::OddDegreeSum { n, d })
+    } else {
+        Ok(())
+    }
+}
+
+// McKay and Wormald's algorithm, removing the loops and double edges of a random pairing with
+// switchings, exactly uniform
+pub struct McKayWormald;
+
+// Draws random pairings until one is simple, exactly uniform but the expected number of pairings
+// drawn grows as exp((d^2 - 1) / 4), so it is only practical for small d
+pub struct ConfigurationModel;
+
+// Steger and Wormald's algorithm, pairing random points one pair at a time whenever they can still
+// be part of a simple graph, fast for larger d but only asymptotically uniform
+pub struct StegerWormald;
+
+// Repeatedly replaces random pairs of edges a -- b, c -- e with a -- c, b -- e when the result is
+// simple, starting from a fixed d-regular graph
+// Uniform in the limit of many steps, 'sweeps' is the number of steps for each edge of the graph
+pub struct SwitchChain {
+    pub sweeps: usize,
+}
+
+impl RegularGenerator for McKayWormald {
+    fn generate(&self, n: usize, d: usize, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error> {
+        check_regular(n, d)?;
+        Ok(ArbitraryGraph::from_neighbours(
+            d,
+            deg::deg(n, d, rng).neighbour_cells(),
+        ))
+    }
+}
+
+impl RegularGenerator for ConfigurationModel {
+    fn generate(&self, n: usize, d: usize, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error> {
+        check_regular(n, d)?;
+        Ok(ArbitraryGraph::from_neighbours(
+            d,
+            deg::configuration(n, d, rng).neighbour_cells(),
+        ))
+    }
+}
+
+impl RegularGenerator for StegerWormald {
+    fn generate(&self, n: usize, d: usize, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error> {
+        check_regular(n, d)?;
+        'restart: loop {
+            // the neighbours of vertex v are in neighbours[v * d..v * d + fill[v]]
+            let mut neighbours = vec![0; n * d];
+            let mut fill = vec![0; n];
+            // the points not yet paired, point p belonging to vertex p / d
+            let mut unpaired: Vec<usize> = (0..n * d).collect();
+            let mut failures = 0;
+            while !unpaired.is_empty() {
+                let suitable = |neighbours: &[usize], fill: &[usize], v: usize, u: usize| {
+                    v != u && !neighbours[v * d..v * d + fill[v]].contains(&u)
+                };
+                let i = rng.gen_range(0..unpaired.len());
+                let j = rng.gen_range(0..unpaired.len());
+                let (v, u) = (unpaired[i] / d, unpaired[j] / d);
+                if suitable(&neighbours, &fill, v, u) {
+                    neighbours[v * d + fill[v]] = u;
+                    neighbours[u * d + fill[u]] = v;
+                    fill[v] += 1;
+                    fill[u] += 1;
+                    unpaired.swap_remove(i.max(j));
+                    unpaired.swap_remove(i.min(j));
+                    failures = 0;
+                } else {
+                    failures += 1;
+                    // check whether any suitable pair is left once more draws have failed than
+                    // there are points left, the scan of all pairs of the u points left costing
+                    // O(u d) per failure amortised, which is small as draws rarely fail until
+                    // few points are left
+                    if failures > unpaired.len() {
+                        if !unpaired
+                            .iter()
+                            .tuple_combinations()
+                            .any(|(&p, &q)| suitable(&neighbours, &fill, p / d, q / d))
+                        {
+                            continue 'restart;
+                        }
+                        failures = 0;
+                    }
+                }
+            }
+            return Ok(ArbitraryGraph::from_neighbours(d, neighbours));
+        }
+    }
+}
+
+impl Default for SwitchChain {
+    fn default() -> Self {
+        SwitchChain { sweeps: 50 }
+    }
+}
+
+impl SwitchChain {
+    // Runs the chain starting from 'graph', which must be regular with at least one edge
+    pub fn run<G: Graph>(&self, graph: &G, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error> {
+        let (n, d) = (graph.n(), graph.d().ok_or(Error::Irregular)?);
+        check_regular(n, d)?;
+        let mut neighbours = vec![0; n * d];
+        for v in graph.iter_vertices() {
+            let v_idx = graph.as_idx(v);
+            for (slot, u) in neighbours[v_idx * d..(v_idx + 1) * d]
+                .iter_mut()
+                .zip(graph.iter_neighbours(v))
+            {
+                *slot = graph.as_idx(u);
+            }
+        }
+        let slot = |neighbours: &[usize], v: usize, u: usize| {
+            v * d
+                + neighbours[v * d..(v + 1) * d]
+                    .iter()
+                    .position(|&x| x == u)
+                    .expect("not an edge")
+        };
+        for _ in 0..self.sweeps * n * d / 2 {
+            // random edges a -- b and c -- e, each direction of each edge being equally likely
+            let (p, q) = (rng.gen_range(0..n * d), rng.gen_range(0..n * d));
+            let (a, b, c, e) = (p / d, neighbours[p], q / d, neighbours[q]);
+            // a != b and c != e as the graph is simple
+            let distinct = a != c && a != e && b != c && b != e;
+            if distinct && slot_free(&neighbours, d, a, c) && slot_free(&neighbours, d, b, e) {
+                let (b_slot, e_slot) = (slot(&neighbours, b, a), slot(&neighbours, e, c));
+                neighbours[p] = c;
+                neighbours[q] = a;
+                neighbours[b_slot] = e;
+                neighbours[e_slot] = b;
+            }
+        }
+        Ok(ArbitraryGraph::from_neighbours(d, neighbours))
+    }
+}
+
+// Whether v and u are not adjacent
+fn slot_free(neighbours: &[usize], d: usize, v: usize, u: usize) -> bool {
+    !neighbours[v * d..(v + 1) * d].contains(&u)
+}
+
+impl RegularGenerator for SwitchChain {
+    fn generate(&self, n: usize, d: usize, rng: &mut impl Rng) -> Result<ArbitraryGraph, Error> {
+        check_regular(n, d)?;
+        // the circulant graph joining v to v +- 1, ..., v +- d / 2, and to v + n / 2 when d is odd
+        let start = (0..n).flat_map(|v| {
+            (1..=d / 2)
+                .flat_map(move |k| [(v + k) % n, (v + n - k) % n])
+                .chain((d % 2 == 1).then_some((v + n / 2) % n))
+        });
+        self.run(&ArbitraryGraph::from_neighbours(d, start), rng)
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for (n, d) in [(10, 3), (28, 3), (50, 4), (30, 7), (8, 7)] {
+        for graph in [
+            ConfigurationModel.generate(n, d.min(4), &mut rng),
+            StegerWormald.generate(n, d, &mut rng),
+            SwitchChain::default().generate(n, d, &mut rng),
+        ] {
+            graph.unwrap().validate(&mut rng).assert_valid();
+        }
+    }
+    StegerWormald
+        .generate(1000, 20, &mut rng)
+        .unwrap()
+        .validate(&mut rng)
+        .assert_valid();
+}
+
+#[test]
+fn test_invalid() {
+    let mut rng = crate::rng::seeded(0);
+    assert_eq!(check_regular(0, 3), Err(Error::NoVertices));
+    assert_eq!(check_regular(5, 0), Err(Error::NoEdges));
+    assert_eq!(check_regular(5, 3), Err(Error::OddDegreeSum { n: 5, d: 3 }));
+    assert_eq!(
+        check_regular(4, 4),
+        Err(Error::DegreeTooLarge { n: 4, d: 4 })
+    );
+    // these used to loop forever
+    assert!(McKayWormald.generate(3, 3, &mut rng).is_err());
+    assert!(StegerWormald.generate(7, 3, &mut rng).is_err());
+    let star = super::StarGraph::new(5).unwrap();
+    assert_eq!(
+        SwitchChain::default().run(&star, &mut rng).err(),
+        Some(Error::Irregular)
+    );
+}
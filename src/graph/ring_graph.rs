@@ -5,9 +5,10 @@ use rand::Rng;
 use smallvec::smallvec;
 
 use crate::graph::Graph;
+use crate::Error;
 
 pub struct RingGraph {
-    pub n: usize,
+    pub(super) n: usize,
 }
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
@@ -37,8 +38,8 @@ impl Graph for RingGraph {
         self.n
     }
 
-    fn d(&self) -> usize {
-        calc_d(self.n)
+    fn d(&self) -> Option<usize> {
+        Some(calc_d(self.n))
     }
 
     fn as_idx(&self, v: Self::Vertex) -> usize {
@@ -46,11 +47,11 @@ impl Graph for RingGraph {
     }
 
     fn iter_vertices(&self) -> Self::VIter {
-        (0..self.n()).map(|x| RingVertex(x))
+        (0..self.n()).map(RingVertex)
     }
 
     fn iter_neighbours(&self, v: RingVertex) -> Self::NIter {
-        match self.d() {
+        match calc_d(self.n) {
             0 => smallvec![],
             1 => smallvec![self.add(v, 1)],
             2 => smallvec![self.add(v, 1), self.add(v, -1)],
@@ -71,6 +72,13 @@ impl Graph for RingGraph {
 }
 
 impl RingGraph {
+    pub fn new(n: usize) -> Result<Self, Error> {
+        match n {
+            0 => Err(Error::NoVertices),
+            _ => Ok(RingGraph { n }),
+        }
+    }
+
     fn add(&self, v: RingVertex, rhs: isize) -> RingVertex {
         RingVertex((v.0 as isize + rhs).rem_euclid(self.n as isize) as usize)
     }
@@ -78,7 +86,9 @@ impl RingGraph {
 
 #[test]
 fn test_valid() {
-    RingGraph { n: 2 }.validate();
-    RingGraph { n: 3 }.validate();
-    RingGraph { n: 4 }.validate();
+    let mut rng = crate::rng::seeded(0);
+    RingGraph { n: 2 }.validate(&mut rng).assert_valid();
+    RingGraph { n: 3 }.validate(&mut rng).assert_valid();
+    RingGraph { n: 4 }.validate(&mut rng).assert_valid();
+    assert_eq!(RingGraph::new(0).err(), Some(Error::NoVertices));
 }
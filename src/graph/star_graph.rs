@@ -0,0 +1,101 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use rand::Rng;
+
+use crate::graph::Graph;
+use crate::Error;
+
+// A centre vertex (0) joined to n - 1 leaves, the simplest irregular graph
+pub struct StarGraph {
+    n: usize,
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+pub struct StarVertex(usize);
+
+impl Display for StarVertex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+const CENTRE: StarVertex = StarVertex(0);
+
+impl Graph for StarGraph {
+    type Vertex = StarVertex;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> StarVertex>;
+    type NIter = std::iter::Map<Range<usize>, fn(usize) -> StarVertex>;
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn d(&self) -> Option<usize> {
+        match self.n {
+            // a single edge is regular
+            2 => Some(1),
+            _ => None,
+        }
+    }
+
+    fn degree(&self, v: Self::Vertex) -> usize {
+        if v == CENTRE {
+            self.n - 1
+        } else {
+            1
+        }
+    }
+
+    fn m(&self) -> usize {
+        self.n - 1
+    }
+
+    fn as_idx(&self, v: Self::Vertex) -> usize {
+        v.0
+    }
+
+    fn iter_vertices(&self) -> Self::VIter {
+        (0..self.n()).map(StarVertex)
+    }
+
+    fn iter_neighbours(&self, v: StarVertex) -> Self::NIter {
+        if v == CENTRE {
+            (1..self.n).map(StarVertex)
+        } else {
+            (0..1).map(StarVertex)
+        }
+    }
+
+    fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
+        v != u && (v == CENTRE || u == CENTRE)
+    }
+
+    fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
+        // each leaf has exactly one edge
+        let leaf = StarVertex(rng.gen_range(1..self.n));
+        if rng.gen() {
+            (leaf, CENTRE)
+        } else {
+            (CENTRE, leaf)
+        }
+    }
+}
+
+impl StarGraph {
+    pub fn new(n: usize) -> Result<Self, Error> {
+        match n {
+            0 => Err(Error::NoVertices),
+            _ => Ok(StarGraph { n }),
+        }
+    }
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    StarGraph { n: 2 }.validate(&mut rng).assert_valid();
+    StarGraph { n: 3 }.validate(&mut rng).assert_valid();
+    StarGraph { n: 10 }.validate(&mut rng).assert_valid();
+    assert_eq!(StarGraph::new(0).err(), Some(Error::NoVertices));
+}
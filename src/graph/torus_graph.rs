@@ -1,96 +1,213 @@
 use std::fmt::{Display, Formatter};
+use std::ops::Range;
 
-use itertools::Itertools;
 use rand::Rng;
-use smallvec::{smallvec, SmallVec};
+use smallvec::SmallVec;
 
 use super::ring_graph::{calc_d, RingVertex};
 use super::{Graph, RingGraph};
+use crate::Error;
 
+// The product of rings with the given side lengths, so k-dimensional for k sides
+// Each vertex is its index, see 'strides'
 pub struct TorusGraph {
-    x: usize,
-    y: usize,
+    sides: Box<[usize]>,
+    strides: Box<[usize]>,
+    n: usize,
+    d: usize,
 }
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
-pub struct TorusVertex(RingVertex, RingVertex);
+pub struct TorusVertex(usize);
 
+// Output writes the coordinates instead, see 'Layout::coordinates'
 impl Display for TorusVertex {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.0, self.1)
+        Display::fmt(&self.0, f)
     }
 }
 
 impl Graph for TorusGraph {
     type Vertex = TorusVertex;
-    type VIter = std::iter::Map<
-        itertools::Product<<RingGraph as Graph>::VIter, <RingGraph as Graph>::VIter>,
-        fn((RingVertex, RingVertex)) -> TorusVertex,
-    >;
-    type NIter = smallvec::IntoIter<[TorusVertex; 4]>;
+    type VIter = std::iter::Map<Range<usize>, fn(usize) -> TorusVertex>;
+    type NIter = smallvec::IntoIter<[TorusVertex; 6]>;
 
     fn n(&self) -> usize {
-        self.x * self.y
+        self.n
     }
 
-    fn d(&self) -> usize {
-        calc_d(self.x) + calc_d(self.y)
+    fn d(&self) -> Option<usize> {
+        Some(self.d)
     }
 
     fn as_idx(&self, v: Self::Vertex) -> usize {
-        v.0 .0 * self.x + v.1 .0
+        v.0
     }
 
     fn iter_vertices(&self) -> Self::VIter {
-        RingGraph { n: self.x }
-            .iter_vertices()
-            .cartesian_product(RingGraph { n: self.y }.iter_vertices())
-            .map(|(vx, vy)| TorusVertex(vx, vy))
+        (0..self.n).map(TorusVertex)
     }
 
     fn iter_neighbours(&self, v: TorusVertex) -> Self::NIter {
-        neighbours(self, v).into_iter()
+        let mut res = SmallVec::new();
+        for dim in 0..self.sides.len() {
+            let c = self.coordinate(v, dim);
+            res.extend(
+                self.ring(dim)
+                    .iter_neighbours(c)
+                    .map(|u| self.moved(v, dim, c, u)),
+            );
+        }
+        res.into_iter()
     }
 
     fn has_edge(&self, v: Self::Vertex, u: Self::Vertex) -> bool {
-        if v.0 == u.0 {
-            RingGraph { n: self.y }.has_edge(v.1, u.1)
-        } else if v.1 == u.1 {
-            RingGraph { n: self.x }.has_edge(v.0, u.0)
-        } else {
-            false
+        // neighbours differ in exactly one coordinate, in which they are neighbours in its ring
+        let mut differing =
+            (0..self.sides.len()).filter(|&dim| self.coordinate(v, dim) != self.coordinate(u, dim));
+        match (differing.next(), differing.next()) {
+            (Some(dim), None) => self
+                .ring(dim)
+                .has_edge(self.coordinate(v, dim), self.coordinate(u, dim)),
+            _ => false,
         }
     }
 
     fn random_edge(&self, rng: &mut impl Rng) -> (Self::Vertex, Self::Vertex) {
-        let vx = RingVertex(rng.gen_range(0..self.x));
-        let vy = RingVertex(rng.gen_range(0..self.y));
-        let v = TorusVertex(vx, vy);
-        let neighbours = neighbours(self, v);
-        (v, neighbours[rng.gen_range(0..neighbours.len())])
+        let v = TorusVertex(rng.gen_range(0..self.n));
+        // the graph is regular, so pick a random one of the d neighbours
+        let mut off = rng.gen_range(0..self.d);
+        for (dim, &side) in self.sides.iter().enumerate() {
+            let d = calc_d(side);
+            if off < d {
+                let c = self.coordinate(v, dim);
+                let u = self.ring(dim).iter_neighbours(c).nth(off).unwrap();
+                return (v, self.moved(v, dim, c, u));
+            }
+            off -= d;
+        }
+        unreachable!()
     }
 }
 
-fn neighbours(g: &TorusGraph, TorusVertex(vx, vy): TorusVertex) -> SmallVec<[TorusVertex; 4]> {
-    let mut res = smallvec![];
-    res.extend(
-        RingGraph { n: g.x }
-            .iter_neighbours(vx)
-            .map(|vx| TorusVertex(vx, vy)),
+impl TorusGraph {
+    // A torus with the given side lengths, sides of length 1 add no edges and sides of length 2
+    // add a single edge rather than two parallel ones
+    pub fn new(sides: &[usize]) -> Result<Self, Error> {
+        let (strides, n) = strides(sides)?;
+        Ok(TorusGraph {
+            sides: sides.into(),
+            strides,
+            n,
+            d: sides.iter().map(|&side| calc_d(side)).sum(),
+        })
+    }
+
+    pub fn sides(&self) -> &[usize] {
+        &self.sides
+    }
+
+    // The coordinates of 'v', one per side
+    pub fn coordinates(&self, v: TorusVertex) -> Vec<usize> {
+        (0..self.sides.len())
+            .map(|dim| self.coordinate(v, dim).0)
+            .collect()
+    }
+
+    fn ring(&self, dim: usize) -> RingGraph {
+        RingGraph { n: self.sides[dim] }
+    }
+
+    fn coordinate(&self, v: TorusVertex, dim: usize) -> RingVertex {
+        RingVertex(v.0 / self.strides[dim] % self.sides[dim])
+    }
+
+    // 'v' with coordinate 'dim', currently 'from', replaced by 'to'
+    fn moved(&self, v: TorusVertex, dim: usize, from: RingVertex, to: RingVertex) -> TorusVertex {
+        TorusVertex(v.0 - from.0 * self.strides[dim] + to.0 * self.strides[dim])
+    }
+}
+
+// Tori, grids and Cayley graphs index each vertex by the mixed radix number whose digits are its
+// coordinates, below 'sides' and the last varying fastest
+// Returns the strides, strides[i] being the product of the sides after i so coordinate i
+// contributes 'coordinate * strides[i]' to the index, and the number of vertices
+pub(super) fn strides(sides: &[usize]) -> Result<(Box<[usize]>, usize), Error> {
+    if sides.contains(&0) {
+        return Err(Error::NoVertices);
+    }
+    let mut strides = vec![0; sides.len()];
+    let mut n: usize = 1;
+    for (stride, &side) in strides.iter_mut().zip(sides).rev() {
+        *stride = n;
+        n = n.checked_mul(side).ok_or(Error::TooManyVertices)?;
+    }
+    Ok((strides.into_boxed_slice(), n))
+}
+
+#[test]
+fn test_valid() {
+    let mut rng = crate::rng::seeded(0);
+    for sides in [
+        &[2, 1][..],
+        &[1, 3],
+        &[2, 5],
+        &[7, 2],
+        &[10, 10],
+        &[3, 5],
+        &[4],
+        &[3, 4, 5],
+        &[2, 2, 2, 2],
+        &[1, 1, 6, 1, 2],
+    ] {
+        TorusGraph::new(sides)
+            .unwrap()
+            .validate(&mut rng)
+            .assert_valid();
+    }
+    assert_eq!(TorusGraph::new(&[0, 5]).err(), Some(Error::NoVertices));
+    assert_eq!(
+        TorusGraph::new(&[0, usize::MAX, 2]).err(),
+        Some(Error::NoVertices)
     );
-    res.extend(
-        RingGraph { n: g.y }
-            .iter_neighbours(vy)
-            .map(|vy| TorusVertex(vx, vy)),
+    assert_eq!(
+        TorusGraph::new(&[usize::MAX, 2]).err(),
+        Some(Error::TooManyVertices)
     );
-    res
 }
 
 #[test]
-fn test_valid() {
-    TorusGraph { x: 2, y: 1 }.validate();
-    TorusGraph { x: 1, y: 3 }.validate();
-    TorusGraph { x: 2, y: 5 }.validate();
-    TorusGraph { x: 7, y: 2 }.validate();
-    TorusGraph { x: 10, y: 10 }.validate();
+fn test_structure() {
+    let graph = TorusGraph::new(&[3, 4, 5]).unwrap();
+    assert_eq!((graph.n(), graph.d(), graph.m()), (60, Some(6), 180));
+    // indexes are mixed radix numbers, the last coordinate varying fastest
+    let v = TorusVertex(2 * 20 + 3 * 5 + 4);
+    assert_eq!(graph.coordinates(v), [2, 3, 4]);
+    assert_eq!(v.to_string(), "59");
+    let mut neighbours: Vec<_> = graph
+        .iter_neighbours(v)
+        .map(|u| graph.coordinates(u))
+        .collect();
+    neighbours.sort();
+    assert_eq!(
+        neighbours,
+        [
+            [0, 3, 4],
+            [1, 3, 4],
+            [2, 0, 4],
+            [2, 2, 4],
+            [2, 3, 0],
+            [2, 3, 3]
+        ]
+    );
+    // sides of length 1 and 2 contribute 0 and 1 to the degree
+    let graph = TorusGraph::new(&[1, 2, 3]).unwrap();
+    assert_eq!((graph.n(), graph.d()), (6, Some(3)));
+    // a hypercube is a torus with sides of length 2
+    let graph = TorusGraph::new(&[2; 4]).unwrap();
+    let cube = super::HyperCubeGraph::new(4).unwrap();
+    assert_eq!(
+        super::CsrGraph::from_graph(&graph),
+        super::CsrGraph::from_graph(&cube)
+    );
 }
@@ -0,0 +1,258 @@
+// Chi-square goodness of fit tests that random graph generators are uniform over labelled
+// d-regular graphs, for n and d small enough that the graphs can be enumerated
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use itertools::Itertools;
+
+use crate::graph::{ArbitraryGraph, ConfigurationModel, Graph, RegularGenerator, SwitchChain};
+use crate::rng::{seeded, SimRng};
+
+// Upper 1e-4 quantile of the standard normal, so a uniform generator fails a test 1 in 10000 times
+// (though each test is seeded so the outcome is fixed)
+const Z: f64 = 3.719;
+
+// Categories expected fewer times than this are merged, as the chi-square approximation needs
+const MIN_EXPECTED: f64 = 5.0;
+
+// The edges of 'graph' as pairs of indexes, sorted, which identifies a labelled graph
+fn canonical<G: Graph>(graph: &G) -> Vec<(usize, usize)> {
+    let mut res: Vec<_> = graph
+        .iter_vertices()
+        .flat_map(|v| {
+            let v_idx = graph.as_idx(v);
+            graph
+                .iter_neighbours(v)
+                .map(move |u| (v_idx, graph.as_idx(u)))
+                .filter(|&(v_idx, u_idx)| v_idx < u_idx)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    res.sort_unstable();
+    res
+}
+
+// Every labelled d-regular graph on n vertices, canonicalised
+fn all_regular(n: usize, d: usize) -> Vec<Vec<(usize, usize)>> {
+    fn extend(
+        pairs: &[(usize, usize)],
+        degrees: &mut [usize],
+        d: usize,
+        edges: &mut Vec<(usize, usize)>,
+        res: &mut Vec<Vec<(usize, usize)>>,
+    ) {
+        let Some((&(u, v), rest)) = pairs.split_first() else {
+            if degrees.iter().all(|&x| x == d) {
+                res.push(edges.clone());
+            }
+            return;
+        };
+        // every pair after a vertex's last pair is larger, so it must be full by then
+        let last = |x: usize| rest.iter().all(|&(a, b)| a != x && b != x);
+        if degrees[u] < d && degrees[v] < d {
+            degrees[u] += 1;
+            degrees[v] += 1;
+            edges.push((u, v));
+            if !(last(u) && degrees[u] < d || last(v) && degrees[v] < d) {
+                extend(rest, degrees, d, edges, res);
+            }
+            edges.pop();
+            degrees[u] -= 1;
+            degrees[v] -= 1;
+        }
+        if !(last(u) && degrees[u] < d || last(v) && degrees[v] < d) {
+            extend(rest, degrees, d, edges, res);
+        }
+    }
+
+    let pairs: Vec<_> = (0..n)
+        .flat_map(|v| (v + 1..n).map(move |u| (v, u)))
+        .collect();
+    let mut res = vec![];
+    extend(&pairs, &mut vec![0; n], d, &mut vec![], &mut res);
+    res
+}
+
+// Upper quantile 'z' of the chi-square distribution with 'df' degrees of freedom, using the
+// Wilson-Hilferty approximation
+fn critical_value(df: usize, z: f64) -> f64 {
+    let df = df as f64;
+    let a = 2.0 / (9.0 * df);
+    df * (1.0 - a + z * a.sqrt()).powi(3)
+}
+
+// Draws 'samples' categories with 'sample' and returns the chi-square statistic against the
+// probabilities 'expected' with its degrees of freedom, rare categories being merged
+fn chi_square<K: Hash + Eq>(
+    expected: &HashMap<K, f64>,
+    samples: usize,
+    mut sample: impl FnMut() -> K,
+) -> (f64, usize) {
+    let mut observed: HashMap<K, usize> = HashMap::new();
+    for _ in 0..samples {
+        let k = sample();
+        assert!(expected.contains_key(&k), "sampled an impossible category");
+        *observed.entry(k).or_default() += 1;
+    }
+    let mut bins = vec![];
+    let (mut rare_expected, mut rare_observed) = (0.0, 0.0);
+    for (k, p) in expected {
+        let (e, o) = (p * samples as f64, *observed.get(k).unwrap_or(&0) as f64);
+        if e < MIN_EXPECTED {
+            rare_expected += e;
+            rare_observed += o;
+        } else {
+            bins.push((e, o));
+        }
+    }
+    if rare_expected > 0.0 {
+        bins.push((rare_expected, rare_observed));
+    }
+    let statistic = bins.iter().map(|&(e, o)| (o - e).powi(2) / e).sum();
+    (statistic, bins.len() - 1)
+}
+
+// Whether 'generate' passes the chi-square test for uniformity over the labelled d-regular graphs
+// on n vertices, using about 'per_graph' samples per graph
+fn is_uniform<G: Graph>(
+    n: usize,
+    d: usize,
+    per_graph: usize,
+    mut generate: impl FnMut(&mut SimRng) -> G,
+) -> bool {
+    let graphs = all_regular(n, d);
+    let p = 1.0 / graphs.len() as f64;
+    let expected = graphs
+        .into_iter()
+        .map(|g| (g, p))
+        .collect::<HashMap<_, _>>();
+    let mut rng = seeded(0);
+    let samples = per_graph * expected.len();
+    let (statistic, df) = chi_square(&expected, samples, || canonical(&generate(&mut rng)));
+    statistic < critical_value(df, Z)
+}
+
+#[test]
+fn test_enumeration() {
+    // known numbers of labelled regular graphs
+    assert_eq!(all_regular(4, 3).len(), 1);
+    assert_eq!(all_regular(5, 2).len(), 12);
+    assert_eq!(all_regular(6, 2).len(), 70);
+    assert_eq!(all_regular(6, 3).len(), 70);
+    assert_eq!(all_regular(7, 2).len(), 465);
+    assert_eq!(all_regular(8, 3).len(), 19355);
+    assert!(all_regular(5, 3).is_empty());
+}
+
+#[test]
+fn test_critical_value() {
+    // 99.9th percentiles from tables
+    assert!((critical_value(10, 3.090) / 29.59 - 1.0).abs() < 0.01);
+    assert!((critical_value(100, 3.090) / 149.45 - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn test_random_uniform() {
+    for (n, d) in [(5, 2), (6, 2), (6, 3), (7, 2)] {
+        assert!(
+            is_uniform(n, d, 50, |rng| ArbitraryGraph::random(n, d, rng).unwrap()),
+            "n = {n}, d = {d}"
+        );
+    }
+}
+
+#[test]
+fn test_alternative_generators_uniform() {
+    for (n, d) in [(5, 2), (6, 2), (6, 3), (7, 2)] {
+        assert!(
+            is_uniform(n, d, 50, |rng| ConfigurationModel
+                .generate(n, d, rng)
+                .unwrap()),
+            "configuration model, n = {n}, d = {d}"
+        );
+    }
+    // a few sweeps are plenty to mix on graphs this small, and the chain is slow to run enough
+    // times for the larger cases
+    let chain = SwitchChain { sweeps: 10 };
+    for (n, d) in [(5, 2), (6, 2), (6, 3)] {
+        assert!(
+            is_uniform(n, d, 50, |rng| chain.generate(n, d, rng).unwrap()),
+            "switch chain, n = {n}, d = {d}"
+        );
+    }
+}
+
+#[test]
+fn test_random_uniform_cycle_types() {
+    // For n large enough that the switchings are used, the labelled 2-regular graphs cannot be
+    // enumerated, but their isomorphism classes, the multisets of cycle lengths, can
+    // A class with c_k cycles of length k contains n! / prod(c_k! (2k)^c_k) labelled graphs
+    fn cycle_types(n: usize, min: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![vec![]];
+        }
+        (min..=n)
+            .flat_map(|k| {
+                cycle_types(n - k, k).into_iter().map(move |mut rest| {
+                    rest.insert(0, k);
+                    rest
+                })
+            })
+            .collect()
+    }
+    fn cycle_type(graph: &ArbitraryGraph) -> Vec<usize> {
+        let mut seen = vec![false; graph.n()];
+        let mut res = vec![];
+        for v in graph.iter_vertices() {
+            let (mut u, mut len) = (v, 0);
+            while !seen[graph.as_idx(u)] {
+                seen[graph.as_idx(u)] = true;
+                len += 1;
+                u = graph
+                    .iter_neighbours(u)
+                    .find(|&w| !seen[graph.as_idx(w)])
+                    .unwrap_or(u);
+            }
+            if len > 0 {
+                res.push(len);
+            }
+        }
+        res.sort_unstable();
+        res
+    }
+
+    let n = 16;
+    let weights: HashMap<Vec<usize>, f64> = cycle_types(n, 3)
+        .into_iter()
+        .map(|lengths| {
+            let w = lengths
+                .iter()
+                .dedup_with_count()
+                .map(|(c, &k)| (1..=c).product::<usize>() as f64 * (2.0 * k as f64).powi(c as i32))
+                .product::<f64>();
+            (lengths, 1.0 / w)
+        })
+        .collect();
+    let total: f64 = weights.values().sum();
+    let expected = weights.into_iter().map(|(k, w)| (k, w / total)).collect();
+    let mut rng = seeded(1);
+    let (statistic, df) = chi_square(&expected, 20000, || {
+        cycle_type(&ArbitraryGraph::random(n, 2, &mut rng).unwrap())
+    });
+    assert!(
+        statistic < critical_value(df, Z),
+        "{statistic} with {df} df"
+    );
+}
+
+#[test]
+fn test_detects_bias() {
+    // a generator that redraws half of the graphs containing the edge 0 -- 1
+    let biased = |rng: &mut SimRng| loop {
+        let graph = ArbitraryGraph::random(6, 3, rng).unwrap();
+        if !canonical(&graph).contains(&(0, 1)) || rand::Rng::gen(rng) {
+            return graph;
+        }
+    };
+    assert!(!is_uniform(6, 3, 50, biased));
+}
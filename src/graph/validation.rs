@@ -0,0 +1,323 @@
+use std::fmt::{Display, Formatter};
+
+use rand::Rng;
+
+use super::Graph;
+
+// Number of random edges sampled to check 'random_edge'
+const RANDOM_EDGES: usize = 100;
+
+// A way in which a graph breaks the contract of 'Graph', vertices are given by their Display form
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    // 'iter_vertices' yields 'count' vertices rather than 'n'
+    VertexCount {
+        count: usize,
+        n: usize,
+    },
+    IndexOutOfRange {
+        v: String,
+        idx: usize,
+    },
+    // two vertices share an index, so 'as_idx' is not a bijection onto 0..n
+    DuplicateIndex {
+        v: String,
+        u: String,
+        idx: usize,
+    },
+    // 'degree' disagrees with the number of neighbours
+    DegreeMismatch {
+        v: String,
+        degree: usize,
+        neighbours: usize,
+    },
+    // a regular graph has a vertex whose degree is not 'd'
+    Irregular {
+        v: String,
+        degree: usize,
+        d: usize,
+    },
+    SelfLoop {
+        v: String,
+    },
+    // u is a neighbour of v but not the other way round
+    AsymmetricEdge {
+        v: String,
+        u: String,
+    },
+    DuplicateNeighbour {
+        v: String,
+        u: String,
+    },
+    // 'has_edge' is false for a pair of neighbours in either order
+    MissingEdge {
+        v: String,
+        u: String,
+    },
+    // 'm' is not half the sum of the degrees
+    EdgeCount {
+        m: usize,
+        degree_sum: usize,
+    },
+    // 'random_edge' returned a pair that are not neighbours
+    RandomNonEdge {
+        v: String,
+        u: String,
+    },
+}
+
+// Every violation found by 'Graph::validate'
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    // Panics listing every violation unless the graph is valid
+    pub fn assert_valid(&self) {
+        assert!(self.is_valid(), "invalid graph:\n{self}");
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::VertexCount { count, n } => {
+                write!(f, "iterated {count} vertices but n is {n}")
+            }
+            Violation::IndexOutOfRange { v, idx } => {
+                write!(f, "vertex {v} has index {idx} which is not below n")
+            }
+            Violation::DuplicateIndex { v, u, idx } => {
+                write!(f, "vertices {v} and {u} both have index {idx}")
+            }
+            Violation::DegreeMismatch {
+                v,
+                degree,
+                neighbours,
+            } => write!(
+                f,
+                "vertex {v} has degree {degree} but {neighbours} neighbours"
+            ),
+            Violation::Irregular { v, degree, d } => {
+                write!(f, "vertex {v} has degree {degree} in a {d}-regular graph")
+            }
+            Violation::SelfLoop { v } => write!(f, "vertex {v} is its own neighbour"),
+            Violation::AsymmetricEdge { v, u } => {
+                write!(f, "{u} is a neighbour of {v} but not the other way round")
+            }
+            Violation::DuplicateNeighbour { v, u } => {
+                write!(f, "{u} appears more than once among the neighbours of {v}")
+            }
+            Violation::MissingEdge { v, u } => {
+                write!(f, "{v} and {u} are neighbours but has_edge is false")
+            }
+            Violation::EdgeCount { m, degree_sum } => {
+                write!(f, "m is {m} but the degrees sum to {degree_sum}")
+            }
+            Violation::RandomNonEdge { v, u } => {
+                write!(f, "random_edge returned {v}, {u} which is not an edge")
+            }
+        }
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for violation in &self.violations {
+            writeln!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn validate<G: Graph + ?Sized>(graph: &G, rng: &mut impl Rng) -> ValidationReport {
+    let mut res = vec![];
+    let n = graph.n();
+    let count = graph.iter_vertices().count();
+    if count != n {
+        res.push(Violation::VertexCount { count, n });
+    }
+    let mut by_idx: Vec<Option<G::Vertex>> = vec![None; n];
+    let mut degree_sum = 0;
+    for v in graph.iter_vertices() {
+        let idx = graph.as_idx(v);
+        match by_idx.get_mut(idx) {
+            None => res.push(Violation::IndexOutOfRange {
+                v: v.to_string(),
+                idx,
+            }),
+            Some(Some(u)) => res.push(Violation::DuplicateIndex {
+                v: v.to_string(),
+                u: u.to_string(),
+                idx,
+            }),
+            Some(slot) => *slot = Some(v),
+        }
+        let degree = graph.degree(v);
+        let neighbours: Vec<_> = graph.iter_neighbours(v).collect();
+        if neighbours.len() != degree {
+            res.push(Violation::DegreeMismatch {
+                v: v.to_string(),
+                degree,
+                neighbours: neighbours.len(),
+            });
+        }
+        if let Some(d) = graph.d().filter(|&d| d != degree) {
+            res.push(Violation::Irregular {
+                v: v.to_string(),
+                degree,
+                d,
+            });
+        }
+        degree_sum += degree;
+        for (i, &u) in neighbours.iter().enumerate() {
+            let pair = || (v.to_string(), u.to_string());
+            if u == v {
+                res.push(Violation::SelfLoop { v: v.to_string() });
+            }
+            if !graph.iter_neighbours(u).any(|w| w == v) {
+                let (v, u) = pair();
+                res.push(Violation::AsymmetricEdge { v, u });
+            }
+            // report each repeated neighbour once, at its first appearance
+            if !neighbours[..i].contains(&u) && neighbours[i + 1..].contains(&u) {
+                let (v, u) = pair();
+                res.push(Violation::DuplicateNeighbour { v, u });
+            }
+            if !graph.has_edge(v, u) || !graph.has_edge(u, v) {
+                let (v, u) = pair();
+                res.push(Violation::MissingEdge { v, u });
+            }
+        }
+    }
+    if degree_sum != 2 * graph.m() {
+        res.push(Violation::EdgeCount {
+            m: graph.m(),
+            degree_sum,
+        });
+    }
+    // graphs without edges have no edges to sample
+    for _ in 0..if degree_sum == 0 { 0 } else { RANDOM_EDGES } {
+        let (v, u) = graph.random_edge(rng);
+        if !graph.iter_neighbours(v).any(|w| w == u) || !graph.has_edge(v, u) {
+            res.push(Violation::RandomNonEdge {
+                v: v.to_string(),
+                u: u.to_string(),
+            });
+        }
+    }
+    ValidationReport { violations: res }
+}
+
+// Asserts that 'random_edge' picks each edge about equally often, drawing 1000 per edge
+#[cfg(test)]
+pub(super) fn assert_random_edge_uniform<G: Graph>(graph: &G, rng: &mut impl Rng) {
+    let mut counts = std::collections::HashMap::new();
+    for _ in 0..1000 * graph.m() {
+        let (v, u) = graph.random_edge(rng);
+        let (v, u) = (graph.as_idx(v), graph.as_idx(u));
+        *counts.entry((v.min(u), v.max(u))).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), graph.m());
+    assert!(
+        counts.values().all(|&c| (850..1150).contains(&c)),
+        "{counts:?}"
+    );
+}
+
+#[test]
+fn test_reports_every_violation() {
+    // adjacency lists that are not simple or symmetric, with an index function that collides
+    struct Broken {
+        neighbours: Vec<Vec<usize>>,
+        idx: fn(usize) -> usize,
+        m: usize,
+    }
+
+    impl Graph for Broken {
+        type Vertex = usize;
+        type VIter = std::ops::Range<usize>;
+        type NIter = std::vec::IntoIter<usize>;
+
+        fn n(&self) -> usize {
+            self.neighbours.len()
+        }
+
+        fn d(&self) -> Option<usize> {
+            Some(2)
+        }
+
+        fn m(&self) -> usize {
+            self.m
+        }
+
+        fn as_idx(&self, v: usize) -> usize {
+            (self.idx)(v)
+        }
+
+        fn iter_vertices(&self) -> Self::VIter {
+            0..self.n()
+        }
+
+        fn iter_neighbours(&self, v: usize) -> Self::NIter {
+            self.neighbours[v].clone().into_iter()
+        }
+
+        fn has_edge(&self, v: usize, u: usize) -> bool {
+            self.neighbours[v].contains(&u)
+        }
+
+        fn random_edge(&self, rng: &mut impl Rng) -> (usize, usize) {
+            (rng.gen_range(0..self.n()), 0)
+        }
+    }
+
+    let mut rng = crate::rng::seeded(0);
+    let square = Broken {
+        neighbours: vec![vec![1, 3], vec![0, 2], vec![1, 3], vec![2, 0]],
+        idx: |v| v,
+        m: 4,
+    };
+    // only random_edge is wrong, returning pairs like 2, 0
+    let report = square.validate(&mut rng);
+    assert!(!report.is_valid());
+    assert!(report
+        .violations
+        .iter()
+        .all(|violation| matches!(violation, Violation::RandomNonEdge { .. })));
+    let broken = Broken {
+        neighbours: vec![vec![1, 1], vec![0, 2], vec![2, 0], vec![]],
+        idx: |v| v / 2 * 4,
+        m: 3,
+    };
+    let report = broken.validate(&mut rng);
+    let s = |x: usize| x.to_string();
+    for violation in [
+        Violation::DuplicateIndex {
+            v: s(1),
+            u: s(0),
+            idx: 0,
+        },
+        Violation::IndexOutOfRange { v: s(2), idx: 4 },
+        Violation::DuplicateNeighbour { v: s(0), u: s(1) },
+        Violation::AsymmetricEdge { v: s(1), u: s(2) },
+        Violation::SelfLoop { v: s(2) },
+        Violation::DegreeMismatch {
+            v: s(3),
+            degree: 2,
+            neighbours: 0,
+        },
+        Violation::EdgeCount {
+            m: 3,
+            degree_sum: 8,
+        },
+    ] {
+        assert!(report.violations.contains(&violation), "{violation}");
+    }
+    assert!(report.violations.len() > 7);
+}
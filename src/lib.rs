@@ -0,0 +1,11 @@
+#![allow(clippy::module_inception)]
+
+pub mod algorithm;
+mod error;
+pub mod experiment;
+pub mod graph;
+pub mod output;
+pub mod rng;
+pub mod spec;
+
+pub use error::Error;
@@ -1,10 +1,309 @@
-use algorithm::{load_balanced, Greedy};
-use graph::ArbitraryGraph;
+use std::io;
+use std::num::NonZeroUsize;
+use std::process::exit;
 
-mod algorithm;
-mod graph;
+use rand::Rng;
+
+use project::algorithm::{Every, GapSeries};
+use project::experiment::{
+    default_threads, run_summaries, run_trials, Family, SummaryWriter, Sweep, Trial, TrialWriter,
+};
+use project::graph::{BinGraph, BinStorage, Graph};
+use project::output::{Format, ResultWriter};
+use project::rng::{repetition_seed, seeded};
+use project::spec::{AlgorithmSpec, BinGraphVisitor, GeneratorSpec, GraphSpec, StorageSpec};
+
+const USAGE: &str = "\
+usage: project [options]
+       project sweep [sweep options]
+       project graph6 FILE [sweep options]
+
+options:
+  --graph SPEC         ring:N, torus:XxY, grid:XxY, hypercube:D, hamming:D:Q, cayley:SIDES:GENS,
+                       margulis:M, lps:P:Q, fat-tree:K, butterfly:K, debruijn:Q:K, ccc:K,
+                       full:N, random:N:D, star:N, gnp:N:P, gnm:N:M, chunglu:N:EXP:AVG, ba:N:M,
+                       geometric:N:K:R or edges:PATH for an edge list file (default
+                       random:28:3), where
+                       - tori and grids take any number of sides, eg. torus:4x4x4
+                       - hamming:D:Q has words of D letters over an alphabet of size Q, and
+                         debruijn:Q:K words of K letters
+                       - cayley graphs are over Z_X x Z_Y x .. for SIDES XxYx.. with comma
+                         separated generators written likewise, eg. cayley:12:1,5 or
+                         cayley:4x6:1x0,0x3, or cayley:SIDES:random:D for a random connection
+                         set of size D
+                       - margulis:M is the Margulis-Gabber-Galil expander on Z_M x Z_M and
+                         lps:P:Q the LPS Ramanujan graph for primes P, Q = 1 mod 4
+                       - fat-tree:K is the K-ary fat-tree including its servers, and
+                         butterfly:K and ccc:K the K-dimensional butterfly and cube-connected
+                         cycles
+                       - random graphs take an optional generator, random:N:D:GEN with GEN
+                         mckay-wormald (default), configuration, steger-wormald or switch
+                       - gnp:N:P and gnm:N:M are Erdős–Rényi graphs with edge probability P or
+                         M edges, chunglu:N:EXP:AVG has power law expected degrees with exponent
+                         EXP averaging AVG, and ba:N:M is Barabási–Albert with M edges per vertex
+                       - geometric:N:K:R joins N random points in the unit K-cube within
+                         distance R, and geometric:N:K:R:torus lets the cube wrap around, DOT
+                         output giving each vertex its point as pos
+                       - giant:SPEC keeps only the largest connected component of SPEC, eg.
+                         giant:gnp:1000:0.002
+  --algorithm NAME     greedy (default greedy)
+  --balls M            number of balls thrown in each repetition (default 280)
+  --repetitions R      number of independent repetitions (default 1)
+  --seed S             seed for the random number generator (default random), repetition 0
+                       uses seed S and repetition i a seed mixed from S and i, so each can be
+                       replayed on its own using the recorded seed
+  --format FORMAT      dot, csv or json (default dot)
+  --storage STORAGE    bin storage, u32, u64 for more than 2^32 balls in a bin, or relative for
+                       one byte per bin when the gap stays below 256 (default u32)
+  --series K           instead of the final loads, write the gap, upper gap and maximum load
+                       after every K balls (csv or json only)
+
+sweep options (lists are comma separated, one result row is written per trial):
+  --family FAMILIES    ring, torus, grid, hypercube, full, random or star (default random), the
+                       torus and grid are n x n and the hypercube has dimension d
+  --n NS               numbers of vertices (default 100)
+  --d DS               degrees (default 3)
+  --balls BALLS        ball counts, eg. 1000, n, 10n or n^2 (default n)
+  --algorithm NAMES    algorithms (default greedy)
+  --repetitions R      repetitions of each configuration (default 1)
+  --seed S             seed for the random number generator (default random), trial 0 uses
+                       seed S and trial i a seed mixed from S and i, recorded in each row
+  --storage STORAGE    bin storage, u32, u64 or relative (default u32)
+  --threads T          number of trials run in parallel (default all cores), results do not
+                       depend on T
+  --format FORMAT      csv or json (default csv)
+
+graph6 runs the sweep over every graph in FILE, one graph6 or sparse6 graph per line, and writes
+the average, minimum and maximum gap over the repetitions of each configuration instead of one
+row per trial. --family, --n and --d do not apply.";
+
+enum Command {
+    Run(Args),
+    Sweep(Vec<Trial>, NonZeroUsize, Format),
+    // trials grouped into consecutive repetitions of each configuration
+    Summary(Vec<Trial>, usize, NonZeroUsize, Format),
+}
+
+struct Args {
+    graph: GraphSpec,
+    algorithm: AlgorithmSpec,
+    balls: u64,
+    repetitions: usize,
+    seed: Option<u64>,
+    format: Format,
+    storage: StorageSpec,
+    series: Option<u64>,
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String>
+where
+    T::Err: ToString,
+{
+    let value = value.ok_or_else(|| format!("missing value for {flag}"))?;
+    value
+        .parse()
+        .map_err(|err: T::Err| format!("{flag}: {}", err.to_string()))
+}
+
+fn parse_list<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<Vec<T>, String>
+where
+    T::Err: ToString,
+{
+    let value: String = parse_value(flag, value)?;
+    value
+        .split(',')
+        .map(|x| parse_value(flag, Some(x.to_string())))
+        .collect()
+}
+
+// Parses the options of a sweep over the graph families, or over 'graphs' if given
+fn parse_sweep(
+    mut args: impl Iterator<Item = String>,
+    graphs: Option<Vec<GraphSpec>>,
+) -> Result<Command, String> {
+    let mut sweep = Sweep {
+        families: vec![Family::Random],
+        ns: vec![100],
+        ds: vec![3],
+        balls: vec!["n".parse()?],
+        algorithms: vec![AlgorithmSpec::Greedy],
+        storage: StorageSpec::Narrow,
+        repetitions: 1,
+        seed: rand::thread_rng().gen(),
+    };
+    let mut threads = default_threads();
+    let mut format = Format::Csv;
+    while let Some(flag) = args.next() {
+        match &*flag {
+            "--family" | "--n" | "--d" if graphs.is_some() => {
+                return Err(format!("{flag} cannot be used with a graph6 file"))
+            }
+            "--family" => sweep.families = parse_list(&flag, args.next())?,
+            "--n" => sweep.ns = parse_list(&flag, args.next())?,
+            "--d" => sweep.ds = parse_list(&flag, args.next())?,
+            "--balls" => sweep.balls = parse_list(&flag, args.next())?,
+            "--algorithm" => sweep.algorithms = parse_list(&flag, args.next())?,
+            "--repetitions" => sweep.repetitions = parse_value(&flag, args.next())?,
+            "--seed" => sweep.seed = parse_value(&flag, args.next())?,
+            "--storage" => sweep.storage = parse_value(&flag, args.next())?,
+            "--threads" => threads = parse_value(&flag, args.next())?,
+            "--format" => format = parse_value(&flag, args.next())?,
+            _ => return Err(format!("unknown option '{flag}'")),
+        }
+    }
+    if format == Format::Dot {
+        return Err("sweep results can only be written as csv or json".to_string());
+    }
+    match graphs {
+        None => Ok(Command::Sweep(sweep.trials()?, threads, format)),
+        Some(graphs) => Ok(Command::Summary(
+            sweep.trials_on(graphs),
+            sweep.repetitions,
+            threads,
+            format,
+        )),
+    }
+}
+
+fn parse_command(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.peekable();
+    if args.peek().map(|x| &**x) == Some("sweep") {
+        args.next();
+        parse_sweep(args, None)
+    } else if args.peek().map(|x| &**x) == Some("graph6") {
+        args.next();
+        let path = args.next().ok_or("graph6: missing file")?;
+        parse_sweep(args, Some(GraphSpec::read_graph6_file(&path)?))
+    } else {
+        parse_args(args).map(Command::Run)
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut res = Args {
+        graph: GraphSpec::Random {
+            n: 28,
+            d: 3,
+            generator: GeneratorSpec::default(),
+        },
+        algorithm: AlgorithmSpec::Greedy,
+        balls: 280,
+        repetitions: 1,
+        seed: None,
+        format: Format::Dot,
+        storage: StorageSpec::Narrow,
+        series: None,
+    };
+    while let Some(flag) = args.next() {
+        match &*flag {
+            "--graph" => res.graph = parse_value(&flag, args.next())?,
+            "--algorithm" => res.algorithm = parse_value(&flag, args.next())?,
+            "--balls" => res.balls = parse_value(&flag, args.next())?,
+            "--repetitions" => res.repetitions = parse_value(&flag, args.next())?,
+            "--seed" => res.seed = Some(parse_value(&flag, args.next())?),
+            "--format" => res.format = parse_value(&flag, args.next())?,
+            "--storage" => res.storage = parse_value(&flag, args.next())?,
+            "--series" => res.series = Some(parse_value(&flag, args.next())?),
+            _ => return Err(format!("unknown option '{flag}'")),
+        }
+    }
+    match res.series {
+        Some(0) => Err("--series: must be positive".to_string()),
+        Some(_) if res.format == Format::Dot => {
+            Err("--series: time series can only be written as csv or json".to_string())
+        }
+        _ => Ok(res),
+    }
+}
+
+// Runs a single repetition on the graph built from the spec and writes out the result
+struct Repetition<'a, W: io::Write> {
+    args: &'a Args,
+    repetition: usize,
+    seed: u64,
+    out: &'a mut ResultWriter<W>,
+}
+
+impl<W: io::Write> BinGraphVisitor for Repetition<'_, W> {
+    type Output = io::Result<()>;
+
+    fn visit<G: Graph, S: BinStorage, R: Rng>(
+        self,
+        mut graph: BinGraph<G, S>,
+        rng: &mut R,
+    ) -> Self::Output {
+        let algorithm = self.args.algorithm;
+        match self.args.series {
+            None => {
+                algorithm.load_balance(&mut graph, self.args.balls, rng);
+                self.out.write(&graph, self.repetition, self.seed)
+            }
+            Some(k) => {
+                let mut series = Every::new(k, GapSeries::new());
+                algorithm.load_balance_observed(&mut graph, self.args.balls, rng, &mut series);
+                self.out
+                    .write_series(&series.into_inner(), self.repetition, self.seed)
+            }
+        }
+    }
+}
+
+fn run(args: &Args) -> io::Result<()> {
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut out = ResultWriter::new(io::stdout().lock(), args.format);
+    for repetition in 0..args.repetitions {
+        let seed = repetition_seed(seed, repetition);
+        args.graph
+            .build_bins(
+                args.storage,
+                &mut seeded(seed),
+                Repetition {
+                    args,
+                    repetition,
+                    seed,
+                    out: &mut out,
+                },
+            )
+            .map_err(io::Error::other)??;
+    }
+    out.finish()?;
+    Ok(())
+}
+
+fn run_sweep(trials: &[Trial], threads: NonZeroUsize, format: Format) -> io::Result<()> {
+    let mut out = TrialWriter::new(io::stdout().lock(), format).map_err(io::Error::other)?;
+    run_trials(trials, threads, &mut out)?;
+    out.finish()
+}
+
+fn run_summary(
+    trials: &[Trial],
+    repetitions: usize,
+    threads: NonZeroUsize,
+    format: Format,
+) -> io::Result<()> {
+    let mut out = SummaryWriter::new(io::stdout().lock(), format).map_err(io::Error::other)?;
+    run_summaries(trials, repetitions, threads, &mut out)?;
+    out.finish()
+}
 
 fn main() {
-    let g = load_balanced::<_, Greedy>(ArbitraryGraph::random(28, 3), 280);
-    println!("{}", g);
+    let command = match parse_command(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            exit(2)
+        }
+    };
+    let res = match command {
+        Command::Run(args) => run(&args),
+        Command::Sweep(trials, threads, format) => run_sweep(&trials, threads, format),
+        Command::Summary(trials, repetitions, threads, format) => {
+            run_summary(&trials, repetitions, threads, format)
+        }
+    };
+    if let Err(err) = res {
+        eprintln!("error: {err}");
+        exit(1)
+    }
 }
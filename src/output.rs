@@ -0,0 +1,245 @@
+use std::fmt::{Display, Formatter, Write as _};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::algorithm::GapSeries;
+use crate::graph::{BinGraph, BinStorage, Graph};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    Dot,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Format::Dot),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown output format '{s}'")),
+        }
+    }
+}
+
+// Formats 'x' as a JSON value, using null for None
+pub fn json_option(x: Option<impl Display>) -> String {
+    x.map_or("null".to_string(), |x| x.to_string())
+}
+
+// Quotes and escapes 's' as a JSON string
+pub fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            c if c.is_control() => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+// The state shared by the writers of each kind of output, CSV needing a header before the first
+// row and JSON records being the elements of an array
+pub struct FormatWriter<W: Write> {
+    out: W,
+    format: Format,
+    written: usize,
+}
+
+impl<W: Write> FormatWriter<W> {
+    pub fn new(out: W, format: Format) -> Self {
+        FormatWriter {
+            out,
+            format,
+            written: 0,
+        }
+    }
+
+    // Fails for DOT, which only suits graphs, 'what' naming the records in the error
+    pub fn tabular(out: W, format: Format, what: &str) -> Result<Self, String> {
+        match format {
+            Format::Csv | Format::Json => Ok(Self::new(out, format)),
+            Format::Dot => Err(format!("{what} can only be written as csv or json")),
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    // The number of records started so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    // Starts the next record, writing 'csv_header' first if it is the first CSV record, and
+    // returns the writer to write the record to
+    pub fn record(&mut self, csv_header: &str) -> io::Result<&mut W> {
+        match self.format {
+            Format::Csv if self.written == 0 => writeln!(self.out, "{csv_header}")?,
+            Format::Json => writeln!(self.out, "{}", if self.written == 0 { "[" } else { "," })?,
+            _ => {}
+        }
+        self.written += 1;
+        Ok(&mut self.out)
+    }
+
+    // Returns the underlying writer without writing any trailing output
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    // Writes any trailing output and flushes the underlying writer
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == Format::Json {
+            if self.written == 0 {
+                write!(self.out, "[")?;
+            }
+            writeln!(self.out, "\n]")?;
+        }
+        self.out.flush()
+    }
+}
+
+// Displays a graph in DOT format with the repetition and seed added to its label
+struct LabelledDot<'a, G: Graph, S: BinStorage> {
+    graph: &'a BinGraph<G, S>,
+    label: String,
+}
+
+impl<G: Graph, S: BinStorage> Display for LabelledDot<'_, G, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.graph.write_dot(f, &self.label)
+    }
+}
+
+// Writes the final state of each repetition of a run in the chosen format
+// DOT writes one graph per repetition, CSV one row per vertex and JSON one object per repetition
+pub struct ResultWriter<W: Write> {
+    inner: FormatWriter<W>,
+}
+
+impl<W: Write> ResultWriter<W> {
+    pub fn new(out: W, format: Format) -> Self {
+        ResultWriter {
+            inner: FormatWriter::new(out, format),
+        }
+    }
+
+    // Writes 'graph', the result of the repetition run with seed 'seed'
+    pub fn write<G: Graph>(
+        &mut self,
+        graph: &BinGraph<G, impl BinStorage>,
+        repetition: usize,
+        seed: u64,
+    ) -> io::Result<()> {
+        let format = self.inner.format();
+        let out = self.inner.record("repetition,seed,vertex,load")?;
+        match format {
+            Format::Dot => write!(
+                out,
+                "{}",
+                LabelledDot {
+                    graph,
+                    label: format!(", repetition = {repetition}, seed = {seed}"),
+                }
+            )?,
+            Format::Csv => {
+                for v in graph.iter_vertices() {
+                    writeln!(
+                        out,
+                        "{repetition},{seed},{},{}",
+                        graph.name(v),
+                        graph.load(v)
+                    )?;
+                }
+            }
+            Format::Json => {
+                write!(
+                    out,
+                    "{{\"repetition\": {repetition}, \"seed\": {seed}, \"n\": {}, \"d\": {}, \"gap\": {}, \"upper_gap\": {}, \"loads\": {{",
+                    graph.n(),
+                    json_option(graph.d()),
+                    graph.gap(),
+                    graph.upper_gap()
+                )?;
+                for (i, v) in graph.iter_vertices().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    write!(
+                        out,
+                        "{sep}{}: {}",
+                        json_string(&graph.name(v)),
+                        graph.load(v)
+                    )?;
+                }
+                write!(out, "}}}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    // Writes the gap over time of the repetition run with seed 'seed'
+    // CSV writes one row per recorded point and JSON one object per repetition
+    pub fn write_series(
+        &mut self,
+        series: &GapSeries,
+        repetition: usize,
+        seed: u64,
+    ) -> io::Result<()> {
+        let format = self.inner.format();
+        if format == Format::Dot {
+            return Err(io::Error::other("time series cannot be written as dot"));
+        }
+        let out = self
+            .inner
+            .record("repetition,seed,step,gap,upper_gap,max_load")?;
+        match format {
+            Format::Dot => unreachable!(),
+            Format::Csv => {
+                for p in series.points() {
+                    writeln!(
+                        out,
+                        "{repetition},{seed},{},{},{},{}",
+                        p.step, p.gap, p.upper_gap, p.max_load
+                    )?;
+                }
+            }
+            Format::Json => {
+                write!(
+                    out,
+                    "{{\"repetition\": {repetition}, \"seed\": {seed}, \"series\": ["
+                )?;
+                for (i, p) in series.points().iter().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    write!(
+                        out,
+                        "{sep}{{\"step\": {}, \"gap\": {}, \"upper_gap\": {}, \"max_load\": {}}}",
+                        p.step, p.gap, p.upper_gap, p.max_load
+                    )?;
+                }
+                write!(out, "]}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    // Writes any trailing output and flushes the underlying writer
+    pub fn finish(self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+#[test]
+fn test_json_string() {
+    assert_eq!(json_string("0.1"), "\"0.1\"");
+    assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+}
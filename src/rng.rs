@@ -0,0 +1,31 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// Random number generator used for simulations, always created from a recorded seed so that
+// any run can be replayed exactly
+pub type SimRng = StdRng;
+
+pub fn seeded(seed: u64) -> SimRng {
+    SimRng::seed_from_u64(seed)
+}
+
+// Seed used for the 'repetition'th repetition of a run with seed 'seed'
+// Repetition 0 uses 'seed' itself, so any repetition can be replayed as a single repetition run
+// using the seed recorded in its output
+// The others mix 'seed' and 'repetition' together, so that runs with nearby seeds, such as
+// consecutive ones, do not share repetitions
+pub fn repetition_seed(seed: u64, repetition: usize) -> u64 {
+    if repetition == 0 {
+        seed
+    } else {
+        splitmix64(splitmix64(seed).wrapping_add(repetition as u64))
+    }
+}
+
+// One step of the SplitMix64 generator, a bijection sending nearby inputs far apart
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,856 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use rand::Rng;
+
+use crate::algorithm::{load_balance, load_balance_observed, Greedy, Observer};
+use crate::graph::{
+    check_regular, graph6_code, parse_graph6, ArbitraryGraph, BarabasiAlbert, BinGraph, BinStorage,
+    ButterflyGraph, CayleyGraph, ChungLu, ConfigurationModel, CsrGraph, CubeConnectedCyclesGraph,
+    DeBruijnGraph, FatTreeGraph, FullGraph, GeometricGraph, Gnm, Gnp, Graph, GridGraph,
+    HammingGraph, HyperCubeGraph, Layout, LpsGraph, MargulisGraph, McKayWormald, NarrowBins,
+    RandomGraphModel, RegularGenerator, RelativeBins, RingGraph, StarGraph, StegerWormald,
+    SwitchChain, TorusGraph, WideBins,
+};
+use crate::Error;
+
+// A graph family and its parameters, selected at runtime (eg. from the command line)
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphSpec {
+    Ring {
+        n: usize,
+    },
+    // any number of sides
+    Torus {
+        sides: Vec<usize>,
+    },
+    // like the torus but without wrapping around
+    Grid {
+        sides: Vec<usize>,
+    },
+    HyperCube {
+        d: u8,
+    },
+    // d letters over an alphabet of size q
+    Hamming {
+        d: usize,
+        q: usize,
+    },
+    // over the product of cyclic groups with the given sides, each generator having one
+    // coordinate per side
+    Cayley {
+        sides: Vec<usize>,
+        generators: Vec<Vec<usize>>,
+    },
+    // likewise with a random connection set of size d
+    RandomCayley {
+        sides: Vec<usize>,
+        d: usize,
+    },
+    // on Z_m x Z_m
+    Margulis {
+        m: usize,
+    },
+    Lps {
+        p: usize,
+        q: usize,
+    },
+    // k-ary, including the servers
+    FatTree {
+        k: usize,
+    },
+    Butterfly {
+        k: u8,
+    },
+    // words of length k over an alphabet of size q
+    DeBruijn {
+        q: usize,
+        k: usize,
+    },
+    CubeConnectedCycles {
+        k: u8,
+    },
+    Full {
+        n: usize,
+    },
+    Random {
+        n: usize,
+        d: usize,
+        generator: GeneratorSpec,
+    },
+    Star {
+        n: usize,
+    },
+    // Erdős–Rényi, each edge present with probability p
+    Gnp {
+        n: usize,
+        p: f64,
+    },
+    // Erdős–Rényi with exactly m edges
+    Gnm {
+        n: usize,
+        m: usize,
+    },
+    // Chung–Lu with power law expected degrees, see 'ChungLu::power_law'
+    ChungLu {
+        n: usize,
+        exponent: f64,
+        average_degree: f64,
+    },
+    // preferential attachment, each new vertex bringing m edges
+    BarabasiAlbert {
+        n: usize,
+        m: usize,
+    },
+    // n random points in [0, 1)^k joined within 'radius', the cube wrapping around if periodic
+    Geometric {
+        n: usize,
+        k: usize,
+        radius: f64,
+        periodic: bool,
+    },
+    // The largest connected component of the graph
+    Giant {
+        spec: Box<GraphSpec>,
+    },
+    // Loaded from an edge list file when the spec is parsed
+    EdgeList {
+        path: String,
+        graph: Arc<CsrGraph>,
+    },
+    // Decoded from a graph6 or sparse6 string
+    Graph6 {
+        code: String,
+        graph: Arc<CsrGraph>,
+    },
+}
+
+// Something that can be done with a graph whose type is only known at runtime
+pub trait GraphVisitor {
+    type Output;
+    // 'rng' is the generator the graph was built with, so a run can continue its stream
+    fn visit<G: Graph, R: Rng>(self, graph: G, rng: &mut R) -> Self::Output;
+    // Like 'visit' for graphs that come with a 'Layout', which is ignored unless overridden
+    fn visit_laid_out<G: Graph, R: Rng>(self, graph: G, layout: Layout, rng: &mut R) -> Self::Output
+    where
+        Self: Sized,
+    {
+        let _ = layout;
+        self.visit(graph, rng)
+    }
+}
+
+// Something that can be done with an empty 'BinGraph' whose graph and storage types are only
+// known at runtime
+pub trait BinGraphVisitor {
+    type Output;
+    fn visit<G: Graph, S: BinStorage, R: Rng>(
+        self,
+        graph: BinGraph<G, S>,
+        rng: &mut R,
+    ) -> Self::Output;
+}
+
+// How the loads of the bins are stored, see 'BinStorage'
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum StorageSpec {
+    #[default]
+    Narrow,
+    Wide,
+    Relative,
+}
+
+struct WithStorage<V> {
+    storage: StorageSpec,
+    visitor: V,
+}
+
+impl<V: BinGraphVisitor> GraphVisitor for WithStorage<V> {
+    type Output = Result<V::Output, Error>;
+
+    fn visit<G: Graph, R: Rng>(self, graph: G, rng: &mut R) -> Self::Output {
+        self.visit_laid_out(graph, Layout::default(), rng)
+    }
+
+    fn visit_laid_out<G: Graph, R: Rng>(
+        self,
+        graph: G,
+        layout: Layout,
+        rng: &mut R,
+    ) -> Self::Output {
+        Ok(match self.storage {
+            StorageSpec::Narrow => self.visitor.visit(
+                BinGraph::<G, NarrowBins>::new(graph)?.with_layout(layout),
+                rng,
+            ),
+            StorageSpec::Wide => self.visitor.visit(
+                BinGraph::<G, WideBins>::new(graph)?.with_layout(layout),
+                rng,
+            ),
+            StorageSpec::Relative => self.visitor.visit(
+                BinGraph::<G, RelativeBins>::new(graph)?.with_layout(layout),
+                rng,
+            ),
+        })
+    }
+}
+
+struct CheckBins;
+
+impl GraphVisitor for CheckBins {
+    type Output = Result<(), Error>;
+
+    fn visit<G: Graph, R: Rng>(self, graph: G, _: &mut R) -> Self::Output {
+        BinGraph::<G>::check(&graph)
+    }
+}
+
+// Converts a graph to a 'CsrGraph', keeping its layout
+struct ToCsr;
+
+impl GraphVisitor for ToCsr {
+    type Output = (CsrGraph, Layout);
+
+    fn visit<G: Graph, R: Rng>(self, graph: G, rng: &mut R) -> Self::Output {
+        self.visit_laid_out(graph, Layout::default(), rng)
+    }
+
+    fn visit_laid_out<G: Graph, R: Rng>(self, graph: G, layout: Layout, _: &mut R) -> Self::Output {
+        (CsrGraph::from_graph(&graph), layout)
+    }
+}
+
+impl GraphSpec {
+    // Builds the graph described by this spec and passes it to 'visitor' along with 'rng'
+    // Only random graph families draw from 'rng'
+    pub fn build<V: GraphVisitor, R: Rng>(
+        &self,
+        rng: &mut R,
+        visitor: V,
+    ) -> Result<V::Output, Error> {
+        Ok(match *self {
+            GraphSpec::EdgeList { ref graph, .. } | GraphSpec::Graph6 { ref graph, .. } => {
+                visitor.visit(CsrGraph::clone(graph), rng)
+            }
+            GraphSpec::Ring { n } => visitor.visit(RingGraph::new(n)?, rng),
+            GraphSpec::Torus { ref sides } => {
+                let graph = TorusGraph::new(sides)?;
+                visitor.visit_laid_out(graph, Layout::coordinates(sides), rng)
+            }
+            GraphSpec::Grid { ref sides } => {
+                let graph = GridGraph::new(sides)?;
+                let layout = Layout::bounded(&graph);
+                visitor.visit_laid_out(graph, layout, rng)
+            }
+            GraphSpec::HyperCube { d } => visitor.visit(HyperCubeGraph::new(d)?, rng),
+            GraphSpec::Hamming { d, q } => visitor.visit(HammingGraph::new(d, q)?, rng),
+            GraphSpec::Cayley {
+                ref sides,
+                ref generators,
+            } => visitor.visit(CayleyGraph::new(sides, generators)?, rng),
+            GraphSpec::RandomCayley { ref sides, d } => {
+                let graph = CayleyGraph::random(sides, d, rng)?;
+                visitor.visit(graph, rng)
+            }
+            GraphSpec::Margulis { m } => visitor.visit(MargulisGraph::new(m)?, rng),
+            GraphSpec::Lps { p, q } => visitor.visit(LpsGraph::new(p, q)?, rng),
+            GraphSpec::FatTree { k } => visitor.visit(FatTreeGraph::new(k)?, rng),
+            GraphSpec::Butterfly { k } => visitor.visit(ButterflyGraph::new(k)?, rng),
+            GraphSpec::DeBruijn { q, k } => visitor.visit(DeBruijnGraph::new(q, k)?, rng),
+            GraphSpec::CubeConnectedCycles { k } => {
+                visitor.visit(CubeConnectedCyclesGraph::new(k)?, rng)
+            }
+            GraphSpec::Full { n } => visitor.visit(FullGraph::new(n)?, rng),
+            GraphSpec::Random { n, d, generator } => {
+                let graph = generator.generate(n, d, rng)?;
+                visitor.visit(graph, rng)
+            }
+            GraphSpec::Star { n } => visitor.visit(StarGraph::new(n)?, rng),
+            GraphSpec::Gnp { n, p } => {
+                let graph = Gnp { n, p }.generate(rng)?;
+                visitor.visit(graph, rng)
+            }
+            GraphSpec::Gnm { n, m } => {
+                let graph = Gnm { n, m }.generate(rng)?;
+                visitor.visit(graph, rng)
+            }
+            GraphSpec::ChungLu {
+                n,
+                exponent,
+                average_degree,
+            } => {
+                let graph = ChungLu::power_law(n, exponent, average_degree)?.generate(rng)?;
+                visitor.visit(graph, rng)
+            }
+            GraphSpec::BarabasiAlbert { n, m } => {
+                let graph = BarabasiAlbert { n, m }.generate(rng)?;
+                visitor.visit(graph, rng)
+            }
+            GraphSpec::Geometric {
+                n,
+                k,
+                radius,
+                periodic,
+            } => {
+                let graph = GeometricGraph::random(n, k, radius, periodic, rng)?;
+                let layout = Layout::embedded(&graph);
+                visitor.visit_laid_out(graph, layout, rng)
+            }
+            GraphSpec::Giant { ref spec } => {
+                let (graph, layout) = spec.build(rng, ToCsr)?;
+                let (giant, kept) = graph.giant_component();
+                visitor.visit_laid_out(giant, layout.restrict(&kept), rng)
+            }
+        })
+    }
+
+    // Builds the graph described by this spec with empty bins stored as selected by 'storage'
+    pub fn build_bins<V: BinGraphVisitor, R: Rng>(
+        &self,
+        storage: StorageSpec,
+        rng: &mut R,
+        visitor: V,
+    ) -> Result<V::Output, Error> {
+        self.build(rng, WithStorage { storage, visitor })?
+    }
+
+    // Fails if 'build_bins' would, without generating random graphs
+    // Random graphs that are not regular may still come out without edges, failing 'build_bins'
+    pub fn check(&self) -> Result<(), Error> {
+        match *self {
+            GraphSpec::Random { n, d, .. } => check_regular(n, d),
+            GraphSpec::Gnp { n, p } => Gnp { n, p }.check(),
+            GraphSpec::Gnm { n, m } => Gnm { n, m }.check(),
+            GraphSpec::ChungLu {
+                n,
+                exponent,
+                average_degree,
+            } => ChungLu::power_law(n, exponent, average_degree)?.check(),
+            GraphSpec::BarabasiAlbert { n, m } => BarabasiAlbert { n, m }.check(),
+            GraphSpec::Geometric { n, k, radius, .. } => GeometricGraph::check_random(n, k, radius),
+            GraphSpec::Giant { ref spec } => spec.check(),
+            GraphSpec::RandomCayley { ref sides, d } => CayleyGraph::check_random(sides, d),
+            GraphSpec::EdgeList { ref graph, .. } | GraphSpec::Graph6 { ref graph, .. } => {
+                BinGraph::<CsrGraph>::check(graph)
+            }
+            // the other graphs are built without drawing from the generator
+            _ => self.build(&mut crate::rng::seeded(0), CheckBins)?,
+        }
+    }
+
+    // Reads a file with one graph6 or sparse6 graph per line, as written by nauty's geng
+    pub fn read_graph6_file(path: &str) -> Result<Vec<GraphSpec>, String> {
+        let file = File::open(path).map_err(|err| format!("{path}: {err}"))?;
+        let mut res = vec![];
+        for (line_idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|err| format!("{path}: {err}"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let code = graph6_code(&line);
+            let graph =
+                parse_graph6(code).map_err(|err| format!("{path}:{}: {err}", line_idx + 1))?;
+            let spec = GraphSpec::Graph6 {
+                code: code.to_string(),
+                graph: Arc::new(graph),
+            };
+            spec.check()
+                .map_err(|err| format!("{path}:{}: {err}", line_idx + 1))?;
+            res.push(spec);
+        }
+        Ok(res)
+    }
+
+    // Name of the graph family, as used in specs
+    pub fn family(&self) -> &'static str {
+        match self {
+            GraphSpec::Ring { .. } => "ring",
+            GraphSpec::Torus { .. } => "torus",
+            GraphSpec::Grid { .. } => "grid",
+            GraphSpec::HyperCube { .. } => "hypercube",
+            GraphSpec::Hamming { .. } => "hamming",
+            GraphSpec::Cayley { .. } | GraphSpec::RandomCayley { .. } => "cayley",
+            GraphSpec::Margulis { .. } => "margulis",
+            GraphSpec::Lps { .. } => "lps",
+            GraphSpec::FatTree { .. } => "fat-tree",
+            GraphSpec::Butterfly { .. } => "butterfly",
+            GraphSpec::DeBruijn { .. } => "debruijn",
+            GraphSpec::CubeConnectedCycles { .. } => "ccc",
+            GraphSpec::Full { .. } => "full",
+            GraphSpec::Random { .. } => "random",
+            GraphSpec::Star { .. } => "star",
+            GraphSpec::Gnp { .. } => "gnp",
+            GraphSpec::Gnm { .. } => "gnm",
+            GraphSpec::ChungLu { .. } => "chunglu",
+            GraphSpec::BarabasiAlbert { .. } => "ba",
+            GraphSpec::Geometric { .. } => "geometric",
+            GraphSpec::Giant { spec } => spec.family(),
+            GraphSpec::EdgeList { .. } => "edges",
+            GraphSpec::Graph6 { .. } => "graph6",
+        }
+    }
+}
+
+fn parse_param<T: FromStr>(spec: &str, param: &str) -> Result<T, String> {
+    param
+        .parse()
+        .map_err(|_| format!("invalid parameter '{param}' in graph spec '{spec}'"))
+}
+
+// Parses side lengths written as eg. '3x4x5'
+fn parse_sides(spec: &str, sides: &str) -> Result<Vec<usize>, String> {
+    sides
+        .split('x')
+        .map(|side| parse_param(spec, side))
+        .collect()
+}
+
+// Parses specs of the form 'ring:N', 'torus:XxY' (or 'torus:XxYxZ' and so on for more
+// dimensions), 'grid:XxY' (likewise), 'hypercube:D', 'hamming:D:Q', 'cayley:SIDES:G1,G2,..'
+// with SIDES and each generator written like the sides of a torus, 'cayley:SIDES:random:D',
+// 'margulis:M', 'lps:P:Q', 'fat-tree:K', 'butterfly:K', 'debruijn:Q:K', 'ccc:K', 'full:N',
+// 'random:N:D', 'random:N:D:GENERATOR', 'star:N', 'gnp:N:P', 'gnm:N:M', 'chunglu:N:EXPONENT:AVG',
+// 'ba:N:M', 'geometric:N:K:R', 'geometric:N:K:R:torus', 'edges:PATH' and 'graph6:CODE', where
+// CODE may also be in sparse6 format, and any of these prefixed with 'giant:'
+// Fails unless balls can be thrown into the graph, see 'check'
+impl FromStr for GraphSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let spec = parse_spec(s)?;
+        spec.check()
+            .map_err(|err| format!("invalid graph spec '{s}': {err}"))?;
+        Ok(spec)
+    }
+}
+
+fn parse_spec(s: &str) -> Result<GraphSpec, String> {
+    if let Some(inner) = s.strip_prefix("giant:") {
+        return Ok(GraphSpec::Giant {
+            spec: Box::new(parse_spec(inner)?),
+        });
+    }
+    if let Some(path) = s.strip_prefix("edges:") {
+        let file = File::open(path).map_err(|err| format!("{path}: {err}"))?;
+        let graph = CsrGraph::read_edge_list(BufReader::new(file))
+            .map_err(|err| format!("{path}: {err}"))?;
+        return Ok(GraphSpec::EdgeList {
+            path: path.to_string(),
+            graph: Arc::new(graph),
+        });
+    }
+    if let Some(code) = s.strip_prefix("graph6:") {
+        return Ok(GraphSpec::Graph6 {
+            code: code.to_string(),
+            graph: Arc::new(parse_graph6(code)?),
+        });
+    }
+    let params: Vec<&str> = s.split(':').collect();
+    match params[..] {
+        ["ring", n] => Ok(GraphSpec::Ring {
+            n: parse_param(s, n)?,
+        }),
+        ["torus", sides] => Ok(GraphSpec::Torus {
+            sides: parse_sides(s, sides)?,
+        }),
+        ["grid", sides] => Ok(GraphSpec::Grid {
+            sides: parse_sides(s, sides)?,
+        }),
+        ["hypercube", d] => Ok(GraphSpec::HyperCube {
+            d: parse_param(s, d)?,
+        }),
+        ["hamming", d, q] => Ok(GraphSpec::Hamming {
+            d: parse_param(s, d)?,
+            q: parse_param(s, q)?,
+        }),
+        ["cayley", sides, "random", d] => Ok(GraphSpec::RandomCayley {
+            sides: parse_sides(s, sides)?,
+            d: parse_param(s, d)?,
+        }),
+        ["cayley", sides, generators] => Ok(GraphSpec::Cayley {
+            sides: parse_sides(s, sides)?,
+            generators: generators
+                .split(',')
+                .map(|generator| parse_sides(s, generator))
+                .collect::<Result<_, _>>()?,
+        }),
+        ["margulis", m] => Ok(GraphSpec::Margulis {
+            m: parse_param(s, m)?,
+        }),
+        ["lps", p, q] => Ok(GraphSpec::Lps {
+            p: parse_param(s, p)?,
+            q: parse_param(s, q)?,
+        }),
+        ["fat-tree", k] => Ok(GraphSpec::FatTree {
+            k: parse_param(s, k)?,
+        }),
+        ["butterfly", k] => Ok(GraphSpec::Butterfly {
+            k: parse_param(s, k)?,
+        }),
+        ["debruijn", q, k] => Ok(GraphSpec::DeBruijn {
+            q: parse_param(s, q)?,
+            k: parse_param(s, k)?,
+        }),
+        ["ccc", k] => Ok(GraphSpec::CubeConnectedCycles {
+            k: parse_param(s, k)?,
+        }),
+        ["full", n] => Ok(GraphSpec::Full {
+            n: parse_param(s, n)?,
+        }),
+        ["random", n, d] => Ok(GraphSpec::Random {
+            n: parse_param(s, n)?,
+            d: parse_param(s, d)?,
+            generator: GeneratorSpec::default(),
+        }),
+        ["random", n, d, generator] => Ok(GraphSpec::Random {
+            n: parse_param(s, n)?,
+            d: parse_param(s, d)?,
+            generator: generator.parse()?,
+        }),
+        ["star", n] => Ok(GraphSpec::Star {
+            n: parse_param(s, n)?,
+        }),
+        ["gnp", n, p] => Ok(GraphSpec::Gnp {
+            n: parse_param(s, n)?,
+            p: parse_param(s, p)?,
+        }),
+        ["gnm", n, m] => Ok(GraphSpec::Gnm {
+            n: parse_param(s, n)?,
+            m: parse_param(s, m)?,
+        }),
+        ["chunglu", n, exponent, average_degree] => Ok(GraphSpec::ChungLu {
+            n: parse_param(s, n)?,
+            exponent: parse_param(s, exponent)?,
+            average_degree: parse_param(s, average_degree)?,
+        }),
+        ["ba", n, m] => Ok(GraphSpec::BarabasiAlbert {
+            n: parse_param(s, n)?,
+            m: parse_param(s, m)?,
+        }),
+        ["geometric", n, k, radius] => Ok(GraphSpec::Geometric {
+            n: parse_param(s, n)?,
+            k: parse_param(s, k)?,
+            radius: parse_param(s, radius)?,
+            periodic: false,
+        }),
+        ["geometric", n, k, radius, "torus"] => Ok(GraphSpec::Geometric {
+            n: parse_param(s, n)?,
+            k: parse_param(s, k)?,
+            radius: parse_param(s, radius)?,
+            periodic: true,
+        }),
+        _ => Err(format!("unknown graph spec '{s}'")),
+    }
+}
+
+impl Display for GraphSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphSpec::Ring { n } => write!(f, "ring:{n}"),
+            GraphSpec::Torus { sides } => write!(f, "torus:{}", sides.iter().join("x")),
+            GraphSpec::Grid { sides } => write!(f, "grid:{}", sides.iter().join("x")),
+            GraphSpec::HyperCube { d } => write!(f, "hypercube:{d}"),
+            GraphSpec::Hamming { d, q } => write!(f, "hamming:{d}:{q}"),
+            GraphSpec::Cayley { sides, generators } => write!(
+                f,
+                "cayley:{}:{}",
+                sides.iter().join("x"),
+                generators
+                    .iter()
+                    .map(|generator| generator.iter().join("x"))
+                    .join(",")
+            ),
+            GraphSpec::RandomCayley { sides, d } => {
+                write!(f, "cayley:{}:random:{d}", sides.iter().join("x"))
+            }
+            GraphSpec::Margulis { m } => write!(f, "margulis:{m}"),
+            GraphSpec::Lps { p, q } => write!(f, "lps:{p}:{q}"),
+            GraphSpec::FatTree { k } => write!(f, "fat-tree:{k}"),
+            GraphSpec::Butterfly { k } => write!(f, "butterfly:{k}"),
+            GraphSpec::DeBruijn { q, k } => write!(f, "debruijn:{q}:{k}"),
+            GraphSpec::CubeConnectedCycles { k } => write!(f, "ccc:{k}"),
+            GraphSpec::Full { n } => write!(f, "full:{n}"),
+            GraphSpec::Random { n, d, generator } => {
+                write!(f, "random:{n}:{d}")?;
+                if *generator != GeneratorSpec::default() {
+                    write!(f, ":{generator}")?;
+                }
+                Ok(())
+            }
+            GraphSpec::Star { n } => write!(f, "star:{n}"),
+            GraphSpec::Gnp { n, p } => write!(f, "gnp:{n}:{p}"),
+            GraphSpec::Gnm { n, m } => write!(f, "gnm:{n}:{m}"),
+            GraphSpec::ChungLu {
+                n,
+                exponent,
+                average_degree,
+            } => write!(f, "chunglu:{n}:{exponent}:{average_degree}"),
+            GraphSpec::BarabasiAlbert { n, m } => write!(f, "ba:{n}:{m}"),
+            GraphSpec::Geometric {
+                n,
+                k,
+                radius,
+                periodic,
+            } => {
+                write!(f, "geometric:{n}:{k}:{radius}")?;
+                if *periodic {
+                    write!(f, ":torus")?;
+                }
+                Ok(())
+            }
+            GraphSpec::Giant { spec } => write!(f, "giant:{spec}"),
+            GraphSpec::EdgeList { path, .. } => write!(f, "edges:{path}"),
+            GraphSpec::Graph6 { code, .. } => write!(f, "graph6:{code}"),
+        }
+    }
+}
+
+impl FromStr for StorageSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "u32" => Ok(StorageSpec::Narrow),
+            "u64" => Ok(StorageSpec::Wide),
+            "relative" => Ok(StorageSpec::Relative),
+            _ => Err(format!("unknown bin storage '{s}'")),
+        }
+    }
+}
+
+impl Display for StorageSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageSpec::Narrow => write!(f, "u32"),
+            StorageSpec::Wide => write!(f, "u64"),
+            StorageSpec::Relative => write!(f, "relative"),
+        }
+    }
+}
+
+// How random regular graphs are generated, see 'RegularGenerator'
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum GeneratorSpec {
+    #[default]
+    McKayWormald,
+    Configuration,
+    StegerWormald,
+    SwitchChain,
+}
+
+impl GeneratorSpec {
+    pub fn generate(
+        &self,
+        n: usize,
+        d: usize,
+        rng: &mut impl Rng,
+    ) -> Result<ArbitraryGraph, Error> {
+        match self {
+            GeneratorSpec::McKayWormald => McKayWormald.generate(n, d, rng),
+            GeneratorSpec::Configuration => ConfigurationModel.generate(n, d, rng),
+            GeneratorSpec::StegerWormald => StegerWormald.generate(n, d, rng),
+            GeneratorSpec::SwitchChain => SwitchChain::default().generate(n, d, rng),
+        }
+    }
+}
+
+impl FromStr for GeneratorSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mckay-wormald" => Ok(GeneratorSpec::McKayWormald),
+            "configuration" => Ok(GeneratorSpec::Configuration),
+            "steger-wormald" => Ok(GeneratorSpec::StegerWormald),
+            "switch" => Ok(GeneratorSpec::SwitchChain),
+            _ => Err(format!("unknown random graph generator '{s}'")),
+        }
+    }
+}
+
+impl Display for GeneratorSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeneratorSpec::McKayWormald => write!(f, "mckay-wormald"),
+            GeneratorSpec::Configuration => write!(f, "configuration"),
+            GeneratorSpec::StegerWormald => write!(f, "steger-wormald"),
+            GeneratorSpec::SwitchChain => write!(f, "switch"),
+        }
+    }
+}
+
+// A load balancing algorithm selected at runtime
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AlgorithmSpec {
+    Greedy,
+}
+
+impl AlgorithmSpec {
+    // Throws 'balls' additional balls into 'graph' using the selected algorithm
+    pub fn load_balance<G: Graph, R: Rng>(
+        &self,
+        graph: &mut BinGraph<G, impl BinStorage>,
+        balls: u64,
+        rng: &mut R,
+    ) {
+        match self {
+            AlgorithmSpec::Greedy => load_balance::<G, Greedy, R>(graph, balls, rng),
+        }
+    }
+
+    // Same as 'load_balance' but notifies 'observer' after each ball is thrown
+    pub fn load_balance_observed<G: Graph, R: Rng, O: Observer<G>>(
+        &self,
+        graph: &mut BinGraph<G, impl BinStorage>,
+        balls: u64,
+        rng: &mut R,
+        observer: &mut O,
+    ) {
+        match self {
+            AlgorithmSpec::Greedy => {
+                load_balance_observed::<G, Greedy, R, O>(graph, balls, rng, observer)
+            }
+        }
+    }
+}
+
+impl FromStr for AlgorithmSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "greedy" => Ok(AlgorithmSpec::Greedy),
+            _ => Err(format!("unknown algorithm '{s}'")),
+        }
+    }
+}
+
+impl Display for AlgorithmSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlgorithmSpec::Greedy => write!(f, "greedy"),
+        }
+    }
+}
+
+#[test]
+fn test_parse_round_trip() {
+    for s in [
+        "ring:10",
+        "torus:4x5",
+        "torus:3x4x5x2",
+        "torus:9",
+        "grid:3x4x5",
+        "hypercube:6",
+        "hamming:3:4",
+        "cayley:10:1,3",
+        "cayley:4x6:1x0,0x3",
+        "cayley:4x6:random:5",
+        "margulis:10",
+        "lps:5:13",
+        "fat-tree:4",
+        "butterfly:3",
+        "debruijn:2:5",
+        "ccc:4",
+        "full:3",
+        "random:28:3",
+        "random:28:3:switch",
+        "random:28:4:steger-wormald",
+        "star:7",
+        "gnp:100:0.05",
+        "gnm:100:150",
+        "chunglu:100:2.5:4",
+        "ba:100:2",
+        "geometric:100:2:0.15",
+        "geometric:100:3:0.3:torus",
+        "giant:geometric:1000:2:0.04",
+        "giant:gnp:100:0.02",
+        "giant:torus:4x5",
+        "graph6:DQc",
+        "graph6::Fa@x^",
+    ] {
+        assert_eq!(s.parse::<GraphSpec>().unwrap().to_string(), s);
+    }
+    assert!("torus:4x".parse::<GraphSpec>().is_err());
+    assert!("ring:ten".parse::<GraphSpec>().is_err());
+    assert!("wheel:5".parse::<GraphSpec>().is_err());
+    assert!("random:28:3:magic".parse::<GraphSpec>().is_err());
+    for invalid in [
+        "ring:0",
+        "ring:1",
+        "torus:0x3",
+        "hypercube:0",
+        "hamming:0:5",
+        "hamming:3:1",
+        "hamming:2:0",
+        "hamming:0:0",
+        "cayley:10:0",
+        "cayley:10:1x1",
+        "cayley:5x3:random:3",
+        "margulis:0",
+        "margulis:1",
+        "lps:7:13",
+        "fat-tree:3",
+        "butterfly:0",
+        "debruijn:1:3",
+        "ccc:0",
+        "full:1",
+        "random:5:3",
+        "gnp:0:0.5",
+        "gnp:10:1.5",
+        "gnm:5:11",
+        "chunglu:10:1:3",
+        "chunglu:10:2.5:-1",
+        "ba:5:5",
+        "ba:5:0",
+        "giant:ring:1",
+        "geometric:0:2:0.1",
+        "geometric:10:2:-1",
+    ] {
+        assert!(invalid.parse::<GraphSpec>().is_err(), "{invalid}");
+    }
+    assert!("random:4:4".parse::<GraphSpec>().is_err());
+    assert_eq!(
+        "random:28:3:mckay-wormald"
+            .parse::<GraphSpec>()
+            .unwrap()
+            .to_string(),
+        "random:28:3"
+    );
+    assert_eq!("greedy".parse(), Ok(AlgorithmSpec::Greedy));
+}
+
+#[test]
+fn test_giant_layout() {
+    // the DOT output and the number of boundary vertices
+    struct Summary;
+
+    impl BinGraphVisitor for Summary {
+        type Output = (String, Option<usize>);
+
+        fn visit<G: Graph, S: BinStorage, R: Rng>(
+            self,
+            graph: BinGraph<G, S>,
+            _: &mut R,
+        ) -> Self::Output {
+            let boundary = graph.region_loads(true).map(|loads| loads.vertices);
+            (graph.to_string(), boundary)
+        }
+    }
+
+    let mut rng = crate::rng::seeded(0);
+    let spec: GraphSpec = "giant:geometric:200:2:0.08".parse().unwrap();
+    let (dot, _) = spec
+        .build_bins(StorageSpec::Narrow, &mut rng, Summary)
+        .unwrap();
+    assert!(dot.contains("pos = "), "{dot}");
+    let spec: GraphSpec = "giant:grid:3x4".parse().unwrap();
+    let (_, boundary) = spec
+        .build_bins(StorageSpec::Narrow, &mut rng, Summary)
+        .unwrap();
+    assert_eq!(boundary, Some(10));
+}